@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum EscrowError {
+    #[msg("Fill amount must be between 1 and the amount still wanted")]
+    InvalidFillAmount,
+    #[msg("Fill is too small to pay out any of the deposit")]
+    FillTooSmall,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("Expiry must be in the future, or 0 for offers that never expire")]
+    InvalidExpiry,
+    #[msg("Offer has expired")]
+    OfferExpired,
+    #[msg("Offer has not expired yet")]
+    OfferNotExpired,
+    #[msg("Receive amount must be greater than 0")]
+    InvalidReceive,
+    #[msg("Withdrawal would leave the vault empty, refund the offer instead")]
+    InvalidDepositChange,
+    #[msg("Offer price changed since it was quoted")]
+    PriceChanged,
+    #[msg("Fee can not exceed 10000 basis points")]
+    InvalidFee,
+    #[msg("Escrow is paused")]
+    Paused,
+    #[msg("Spread must be within +/- 5000 basis points")]
+    InvalidSpread,
+    #[msg("Oracle-priced offers need their price feed account")]
+    MissingPriceFeed,
+    #[msg("Oracle price is not positive")]
+    InvalidPrice,
+    #[msg("Oracle price is too old")]
+    StalePrice,
+    #[msg("Not supported for oracle-priced offers")]
+    OraclePricedOffer,
+    #[msg("Offer is not the best-priced entry in the order book")]
+    NotBestOffer,
+    #[msg("Offers are not opposite sides of the same pair")]
+    MismatchedOffers,
+    #[msg("Offer prices do not cross")]
+    PricesDoNotCross,
+    #[msg("Matching fee is more than the crossing surplus")]
+    MatchingFeeTooHigh,
+    #[msg("Matching fee needs the caller's mint_b token account")]
+    MissingFeeAccount,
+    #[msg("Only the program's upgrade authority can initialize the config")]
+    NotUpgradeAuthority,
+    #[msg("Oracle-priced offers need the most the taker is willing to pay")]
+    MissingPriceBound,
+}
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface}};
+
+use crate::{error::EscrowError, instructions::return_vault_and_close, state::{Escrow, OrderBook, ESCROW_VERSION}};
+
+// lamports taken out of the escrow rent for whoever cleans up an expired offer
+pub const CLOSE_EXPIRED_BOUNTY: u64 = 100_000;
+
+#[derive(Accounts)]
+pub struct CloseExpired<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        close = maker,
+        has_one = mint_a,
+        has_one = maker,
+        seeds = [b"escrow", [ESCROW_VERSION].as_ref(), maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.is_expired(Clock::get()?.unix_timestamp) @ EscrowError::OfferNotExpired,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    // offers made before order books existed can be settled without one
+    #[account(
+        mut,
+        seeds = [b"order_book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = order_book.load()?.bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+//Anyone can return an expired offer to its maker
+impl<'info> CloseExpired<'info> {
+    pub fn pay_bounty(&mut self) -> Result<()> {
+        // the rest of the escrow rent goes back to the maker on close
+        let escrow = self.escrow.to_account_info();
+        let bounty = CLOSE_EXPIRED_BOUNTY.min(escrow.lamports());
+
+        **escrow.try_borrow_mut_lamports()? -= bounty;
+        **self.caller.to_account_info().try_borrow_mut_lamports()? += bounty;
+
+        Ok(())
+    }
+
+    pub fn return_and_close_vault(&mut self) -> Result<()> {
+        if let Some(order_book) = &self.order_book {
+            order_book.load_mut()?.remove(&self.escrow.key());
+        }
+
+        return_vault_and_close(
+            &self.escrow,
+            &self.vault,
+            &self.maker_ata_a,
+            &self.mint_a,
+            &self.maker.to_account_info(),
+            &self.token_program,
+        )
+    }
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::EscrowError, program::AnchorEscrow, state::{Config, MAX_FEE_BPS}};
+
+//Only the program's upgrade authority can claim the admin role
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, AnchorEscrow>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ EscrowError::NotUpgradeAuthority)]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"config"],
+        bump,
+        space = 8 + Config::INIT_SPACE,
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeConfig<'info> {
+    pub fn init_config(&mut self, fee_bps: u16, treasury: Pubkey, bumps: &InitializeConfigBumps) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, EscrowError::InvalidFee);
+
+        self.config.set_inner(Config {
+            admin: self.admin.key(),
+            fee_bps,
+            treasury,
+            paused: false,
+            bump: bumps.config,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+//Only the fields passed in are changed
+impl<'info> UpdateConfig<'info> {
+    pub fn update_config(&mut self, fee_bps: Option<u16>, treasury: Option<Pubkey>, paused: Option<bool>) -> Result<()> {
+        if let Some(fee_bps) = fee_bps {
+            require!(fee_bps <= MAX_FEE_BPS, EscrowError::InvalidFee);
+            self.config.fee_bps = fee_bps;
+        }
+        if let Some(treasury) = treasury {
+            self.config.treasury = treasury;
+        }
+        if let Some(paused) = paused {
+            self.config.paused = paused;
+        }
+
+        Ok(())
+    }
+}
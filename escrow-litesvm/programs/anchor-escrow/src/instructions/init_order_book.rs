@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::OrderBook;
+
+// Creates the (empty) book of a pair whose offers were all made before order books existed,
+// so take_best works on the pair again once new offers list. Anyone can pay for it
+#[derive(Accounts)]
+pub struct InitOrderBook<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    pub mint_b: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"order_book", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump,
+        space = OrderBook::SPACE,
+    )]
+    pub order_book: AccountLoader<'info, OrderBook>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitOrderBook<'info> {
+    pub fn init_order_book(&mut self, bumps: &InitOrderBookBumps) -> Result<()> {
+        let mut order_book = self.order_book.load_init()?;
+        order_book.mint_a = self.mint_a.key();
+        order_book.mint_b = self.mint_b.key();
+        order_book.bump = bumps.order_book;
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked}};
 
-use crate::state::Escrow;
+use crate::{error::EscrowError, state::{Config, Escrow, OrderBook, ESCROW_VERSION, MAX_SPREAD_BPS}};
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -19,7 +19,7 @@ pub struct Make<'info> {
     #[account(
         init,
         payer = maker,
-        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        seeds = [b"escrow", [ESCROW_VERSION].as_ref(), maker.key().as_ref(), seed.to_le_bytes().as_ref()],
         bump,
         space = 8 + Escrow::INIT_SPACE,
     )]
@@ -31,25 +31,63 @@ pub struct Make<'info> {
         associated_token::authority = escrow,
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = maker,
+        seeds = [b"order_book", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump,
+        space = OrderBook::SPACE,
+    )]
+    pub order_book: AccountLoader<'info, OrderBook>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ EscrowError::Paused,
+    )]
+    pub config: Account<'info, Config>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Make<'info> {
-    pub fn init_escrow(&mut self, seed: u64, receive: u64, bumps: &MakeBumps) -> Result<()> {
+    pub fn init_escrow(&mut self, seed: u64, receive: u64, expiry: i64, bumps: &MakeBumps) -> Result<()> {
+        require!(
+            expiry == 0 || expiry > Clock::get()?.unix_timestamp,
+            EscrowError::InvalidExpiry
+        );
+
         self.escrow.set_inner(Escrow {
             seed,
             maker: self.maker.key(),
             mint_a: self.mint_a.key(),
             mint_b: self.mint_b.key(),
             receive,
+            remaining_deposit: 0,
+            expiry,
+            price_feed: Pubkey::default(),
+            spread_bps: 0,
             bump: bumps.escrow,
         });
 
         Ok(())
     }
 
+    // the taker pays the price feed's quote for the whole vault, adjusted by the spread
+    pub fn init_oracle_escrow(&mut self, seed: u64, price_feed: Pubkey, spread_bps: i16, expiry: i64, bumps: &MakeBumps) -> Result<()> {
+        require!(
+            (-MAX_SPREAD_BPS..=MAX_SPREAD_BPS).contains(&spread_bps),
+            EscrowError::InvalidSpread
+        );
+        require!(price_feed != Pubkey::default(), EscrowError::MissingPriceFeed);
+
+        self.init_escrow(seed, 0, expiry, bumps)?;
+        self.escrow.price_feed = price_feed;
+        self.escrow.spread_bps = spread_bps;
+
+        Ok(())
+    }
+
     pub fn deposit(&mut self, deposit: u64) -> Result<()> {
         let cpi_program = self.token_program.to_account_info();
 
@@ -64,6 +102,30 @@ impl<'info> Make<'info> {
 
         transfer_checked(cpi_ctx, deposit, self.mint_a.decimals)?;
 
+        // track what actually landed in the vault, transfer fees included
+        self.vault.reload()?;
+        self.escrow.remaining_deposit = self.vault.amount;
+
+        Ok(())
+    }
+
+    // oracle-priced offers have no fixed price to sort by and are not listed
+    pub fn list(&mut self, bumps: &MakeBumps) -> Result<()> {
+        let mut order_book = match self.order_book.load_mut() {
+            Ok(order_book) => order_book,
+            Err(_) => {
+                let mut order_book = self.order_book.load_init()?;
+                order_book.mint_a = self.mint_a.key();
+                order_book.mint_b = self.mint_b.key();
+                order_book.bump = bumps.order_book;
+                order_book
+            }
+        };
+
+        if !self.escrow.is_oracle_priced() {
+            order_book.insert(self.escrow.order_entry(self.escrow.key()));
+        }
+
         Ok(())
     }
-}
\ No newline at end of file
+}
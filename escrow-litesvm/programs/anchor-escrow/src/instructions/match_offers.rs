@@ -0,0 +1,263 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked}};
+
+use crate::{error::EscrowError, instructions::return_vault_and_close, state::{Config, Escrow, OrderBook, ESCROW_VERSION}};
+
+//Two opposite offers, escrow_x sells mint_a for mint_b and escrow_y sells mint_b for mint_a
+#[derive(Accounts)]
+pub struct MatchOffers<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub maker_x: SystemAccount<'info>,
+    #[account(mut)]
+    pub maker_y: SystemAccount<'info>,
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_a,
+        has_one = mint_b,
+        constraint = escrow_x.maker == maker_x.key() @ EscrowError::MismatchedOffers,
+        seeds = [b"escrow", [ESCROW_VERSION].as_ref(), maker_x.key().as_ref(), escrow_x.seed.to_le_bytes().as_ref()],
+        bump = escrow_x.bump,
+    )]
+    pub escrow_x: Box<Account<'info, Escrow>>,
+    #[account(
+        mut,
+        constraint = escrow_y.maker == maker_y.key() @ EscrowError::MismatchedOffers,
+        constraint = escrow_y.mint_a == mint_b.key() @ EscrowError::MismatchedOffers,
+        constraint = escrow_y.mint_b == mint_a.key() @ EscrowError::MismatchedOffers,
+        seeds = [b"escrow", [ESCROW_VERSION].as_ref(), maker_y.key().as_ref(), escrow_y.seed.to_le_bytes().as_ref()],
+        bump = escrow_y.bump,
+    )]
+    pub escrow_y: Box<Account<'info, Escrow>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow_x,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = escrow_y,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker_x,
+    )]
+    pub maker_x_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker_x,
+    )]
+    pub maker_x_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker_y,
+    )]
+    pub maker_y_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker_y,
+    )]
+    pub maker_y_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    // offers made before order books existed can be settled without one
+    #[account(
+        mut,
+        seeds = [b"order_book", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump = order_book_x.load()?.bump,
+    )]
+    pub order_book_x: Option<AccountLoader<'info, OrderBook>>,
+    #[account(
+        mut,
+        seeds = [b"order_book", mint_b.key().as_ref(), mint_a.key().as_ref()],
+        bump = order_book_y.load()?.bump,
+    )]
+    pub order_book_y: Option<AccountLoader<'info, OrderBook>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ EscrowError::Paused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(address = config.treasury)]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_b,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    // only needed when the caller asks for a matching fee
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = caller,
+    )]
+    pub caller_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+//Fill as much of both offers as possible at x's price
+//y pays at most its own price, the difference is the crossing surplus
+//The caller can keep up to the surplus as a matching fee, y keeps the rest
+impl<'info> MatchOffers<'info> {
+    // (mint_a moving to maker_y, mint_b owed to maker_x)
+    pub fn crossing(&self, matching_fee: u64) -> Result<(u64, u64)> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            !self.escrow_x.is_oracle_priced() && !self.escrow_y.is_oracle_priced(),
+            EscrowError::OraclePricedOffer
+        );
+        require!(
+            !self.escrow_x.is_expired(now) && !self.escrow_y.is_expired(now),
+            EscrowError::OfferExpired
+        );
+
+        let amount_a = self.vault_x.amount.min(self.escrow_y.receive);
+
+        // x's price rounded up in its favour, y's rounded down in its favour
+        let owed_to_x = (self.escrow_x.receive as u128)
+            .checked_mul(amount_a as u128)
+            .ok_or(EscrowError::Overflow)?
+            .div_ceil(self.vault_x.amount.max(1) as u128);
+        let most_from_y = (self.vault_y.amount as u128)
+            .checked_mul(amount_a as u128)
+            .ok_or(EscrowError::Overflow)?
+            / self.escrow_y.receive.max(1) as u128;
+
+        require!(
+            amount_a > 0 && owed_to_x > 0 && owed_to_x <= most_from_y,
+            EscrowError::PricesDoNotCross
+        );
+        require!(
+            matching_fee as u128 <= most_from_y - owed_to_x,
+            EscrowError::MatchingFeeTooHigh
+        );
+        require!(
+            matching_fee == 0 || self.caller_ata_b.is_some(),
+            EscrowError::MissingFeeAccount
+        );
+
+        Ok((amount_a, owed_to_x as u64))
+    }
+
+    pub fn swap(&mut self, amount_a: u64, owed_to_x: u64, matching_fee: u64) -> Result<()> {
+        // the venue fee comes out of what maker_x receives, as on take
+        let fee = self.config.fee(owed_to_x)?;
+
+        self.transfer_from_vault(true, self.maker_y_ata_a.to_account_info(), amount_a)?;
+        self.transfer_from_vault(false, self.maker_x_ata_b.to_account_info(), owed_to_x - fee)?;
+        if fee > 0 {
+            self.transfer_from_vault(false, self.treasury_ata_b.to_account_info(), fee)?;
+        }
+        if let Some(caller_ata_b) = self.caller_ata_b.as_ref().filter(|_| matching_fee > 0) {
+            self.transfer_from_vault(false, caller_ata_b.to_account_info(), matching_fee)?;
+        }
+
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+
+        Ok(())
+    }
+
+    pub fn settle_x(&mut self, owed_to_x: u64) -> Result<()> {
+        self.escrow_x.receive -= owed_to_x;
+        self.escrow_x.remaining_deposit = self.vault_x.amount;
+
+        let key = self.escrow_x.key();
+        if let Some(order_book) = &self.order_book_x {
+            let mut order_book = order_book.load_mut()?;
+            if self.escrow_x.receive > 0 {
+                order_book.update(self.escrow_x.order_entry(key));
+            } else {
+                order_book.remove(&key);
+            }
+        }
+        if self.escrow_x.receive > 0 {
+            return Ok(());
+        }
+
+        return_vault_and_close(
+            &self.escrow_x,
+            &self.vault_x,
+            &self.maker_x_ata_a,
+            &self.mint_a,
+            &self.maker_x.to_account_info(),
+            &self.token_program,
+        )?;
+        self.escrow_x.close(self.maker_x.to_account_info())
+    }
+
+    pub fn settle_y(&mut self, amount_a: u64) -> Result<()> {
+        self.escrow_y.receive -= amount_a;
+        self.escrow_y.remaining_deposit = self.vault_y.amount;
+
+        let key = self.escrow_y.key();
+        if let Some(order_book) = &self.order_book_y {
+            let mut order_book = order_book.load_mut()?;
+            if self.escrow_y.receive > 0 {
+                order_book.update(self.escrow_y.order_entry(key));
+            } else {
+                order_book.remove(&key);
+            }
+        }
+        if self.escrow_y.receive > 0 {
+            return Ok(());
+        }
+
+        // whatever surplus y kept goes back with the rest of its vault
+        return_vault_and_close(
+            &self.escrow_y,
+            &self.vault_y,
+            &self.maker_y_ata_b,
+            &self.mint_b,
+            &self.maker_y.to_account_info(),
+            &self.token_program,
+        )?;
+        self.escrow_y.close(self.maker_y.to_account_info())
+    }
+
+    fn transfer_from_vault(&self, from_x: bool, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let (escrow, vault, mint) = if from_x {
+            (&self.escrow_x, &self.vault_x, &self.mint_a)
+        } else {
+            (&self.escrow_y, &self.vault_y, &self.mint_b)
+        };
+
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            &[ESCROW_VERSION],
+            escrow.maker.as_ref(),
+            &escrow.seed.to_le_bytes()[..],
+            &[escrow.bump]
+        ]];
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from: vault.to_account_info(),
+            to,
+            authority: escrow.to_account_info(),
+            mint: mint.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+
+        transfer_checked(cpi_context, amount, mint.decimals)
+    }
+}
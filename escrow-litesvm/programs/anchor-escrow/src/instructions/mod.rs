@@ -1,7 +1,21 @@
+pub mod close_expired;
+pub mod config;
+pub mod init_order_book;
 pub mod make;
+pub mod match_offers;
 pub mod refund;
+pub mod refund_legacy;
 pub mod take;
+pub mod take_partial;
+pub mod update_offer;
 
+pub use close_expired::*;
+pub use config::*;
+pub use init_order_book::*;
 pub use make::*;
+pub use match_offers::*;
 pub use refund::*;
-pub use take::*;
\ No newline at end of file
+pub use refund_legacy::*;
+pub use take::*;
+pub use take_partial::*;
+pub use update_offer::*;
\ No newline at end of file
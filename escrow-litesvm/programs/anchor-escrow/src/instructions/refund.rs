@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked, CloseAccount, close_account};
 
-use crate::state::Escrow;
+use crate::state::{Escrow, OrderBook, ESCROW_VERSION};
 
 #[derive(Accounts)]
 pub struct Refund<'info> {
@@ -19,7 +19,7 @@ pub struct Refund<'info> {
         close = maker,
         has_one = mint_a,
         has_one = maker,
-        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        seeds = [b"escrow", [ESCROW_VERSION].as_ref(), maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
         bump = escrow.bump,
     )]
     pub escrow: Account<'info, Escrow>,
@@ -29,44 +29,87 @@ pub struct Refund<'info> {
         associated_token::authority = escrow,
     )]
     vault: InterfaceAccount<'info, TokenAccount>,
+    // offers made before order books existed can be settled without one
+    #[account(
+        mut,
+        seeds = [b"order_book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = order_book.load()?.bump,
+    )]
+    order_book: Option<AccountLoader<'info, OrderBook>>,
     token_program: Interface<'info, TokenInterface>,
     system_program: Program<'info, System>,
 }
 
 impl<'info> Refund<'info> {
     pub fn refund_and_close_vault(&mut self) -> Result<()> {
-        let signer_seeds: [&[&[u8]]; 1] = [&[
-            b"escrow",
-            self.maker.key.as_ref(),
-            &self.escrow.seed.to_le_bytes()[..],
-            &[self.escrow.bump]
-        ]];
-        
-        let cpi_program = self.token_program.to_account_info();
+        if let Some(order_book) = &self.order_book {
+            order_book.load_mut()?.remove(&self.escrow.key());
+        }
+
+        return_vault_and_close(
+            &self.escrow,
+            &self.vault,
+            &self.maker_ata_a,
+            &self.mint_a,
+            &self.maker.to_account_info(),
+            &self.token_program,
+        )
+    }
+}
 
-        let cpi_accounts = TransferChecked {
-            from: self.vault.to_account_info(),
-            to: self.maker_ata_a.to_account_info(),
-            mint: self.mint_a.to_account_info(),
-            authority: self.escrow.to_account_info(),
-        };
+//Send everything left in the vault back to the maker and close the vault to the maker
+//Shared by refund and close_expired
+pub fn return_vault_and_close<'info>(
+    escrow: &Account<'info, Escrow>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    maker_ata_a: &InterfaceAccount<'info, TokenAccount>,
+    mint_a: &InterfaceAccount<'info, Mint>,
+    maker: &AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    let signer_seeds: [&[&[u8]]; 1] = [&[
+        b"escrow",
+        &[ESCROW_VERSION],
+        maker.key.as_ref(),
+        &escrow.seed.to_le_bytes()[..],
+        &[escrow.bump]
+    ]];
 
-        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+    return_vault_with_seeds(&escrow.to_account_info(), &signer_seeds, vault, maker_ata_a, mint_a, maker, token_program)
+}
 
-        transfer_checked(cpi_context, self.vault.amount, self.mint_a.decimals)?;
+//Same for any escrow layout, `escrow` signs with `signer_seeds`
+pub fn return_vault_with_seeds<'info>(
+    escrow: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    maker_ata_a: &InterfaceAccount<'info, TokenAccount>,
+    mint_a: &InterfaceAccount<'info, Mint>,
+    maker: &AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    let cpi_program = token_program.to_account_info();
 
-        let cpi_program = self.token_program.to_account_info();
+    let cpi_accounts = TransferChecked {
+        from: vault.to_account_info(),
+        to: maker_ata_a.to_account_info(),
+        mint: mint_a.to_account_info(),
+        authority: escrow.clone(),
+    };
 
-        let cpi_accounts = CloseAccount {
-            account: self.vault.to_account_info(),
-            destination: self.maker.to_account_info(),
-            authority: self.escrow.to_account_info(),
-        };
+    let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
 
-        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+    transfer_checked(cpi_context, vault.amount, mint_a.decimals)?;
 
-        close_account(cpi_context)?;
-        
-        Ok(())
-    }
-}
\ No newline at end of file
+    let cpi_program = token_program.to_account_info();
+
+    let cpi_accounts = CloseAccount {
+        account: vault.to_account_info(),
+        destination: maker.clone(),
+        authority: escrow.clone(),
+    };
+
+    let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+    close_account(cpi_context)
+}
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked, CloseAccount, close_account}};
 
-use crate::state::Escrow;
+use crate::{error::EscrowError, state::{Config, Escrow, OrderBook, PriceFeed, ESCROW_VERSION}};
 
 //Create context
 #[derive(Accounts)]
@@ -38,7 +38,7 @@ pub struct Take<'info> {
         has_one = maker,
         has_one = mint_a,
         has_one = mint_b,
-        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        seeds = [b"escrow", [ESCROW_VERSION].as_ref(), maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
         bump = escrow.bump,
     )]
     pub escrow: Account<'info, Escrow>,
@@ -48,6 +48,30 @@ pub struct Take<'info> {
         associated_token::authority = escrow,
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
+    // offers made before order books existed can be settled without one
+    #[account(
+        mut,
+        seeds = [b"order_book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = order_book.load()?.bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    #[account(address = escrow.price_feed @ EscrowError::MissingPriceFeed)]
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ EscrowError::Paused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(address = config.treasury)]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
@@ -57,7 +81,65 @@ pub struct Take<'info> {
 //Transfer tokens from vault to taker
 //Close vault account
 impl<'info> Take<'info> {
-    pub fn deposit(&mut self) -> Result<()> {
+    pub fn check_not_expired(&self) -> Result<()> {
+        require!(
+            !self.escrow.is_expired(Clock::get()?.unix_timestamp),
+            EscrowError::OfferExpired
+        );
+        Ok(())
+    }
+
+    // fixed receive amount, or the oracle quote for the whole vault
+    pub fn amount_owed(&self) -> Result<u64> {
+        if !self.escrow.is_oracle_priced() {
+            return Ok(self.escrow.receive);
+        }
+
+        let price_feed = self.price_feed.as_ref().ok_or(EscrowError::MissingPriceFeed)?;
+        self.escrow.oracle_amount_owed(self.vault.amount, price_feed, Clock::get()?.unix_timestamp)
+    }
+
+    // takers quoting a price get protected against the maker changing it under them,
+    // for oracle-priced offers expected_receive is the most the taker is willing to pay
+    // and is required, the maker picks the feed
+    pub fn check_price(&self, expected_receive: Option<u64>, amount_owed: u64) -> Result<()> {
+        if self.escrow.is_oracle_priced() {
+            let expected_receive = expected_receive.ok_or(EscrowError::MissingPriceBound)?;
+            require_gte!(expected_receive, amount_owed, EscrowError::PriceChanged);
+        } else if let Some(expected_receive) = expected_receive {
+            require_eq!(amount_owed, expected_receive, EscrowError::PriceChanged);
+        }
+        Ok(())
+    }
+
+    // take_best only fills the head of the book, and never for more than max_receive
+    pub fn check_best(&self, max_receive: u64, amount_owed: u64) -> Result<()> {
+        let order_book = self.order_book.as_ref().ok_or(EscrowError::NotBestOffer)?.load()?;
+        let best = order_book.best().ok_or(EscrowError::NotBestOffer)?;
+        require_keys_eq!(best.escrow, self.escrow.key(), EscrowError::NotBestOffer);
+        require_gte!(max_receive, amount_owed, EscrowError::PriceChanged);
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, amount_owed: u64) -> Result<()> {
+        // the venue fee comes out of what the maker receives
+        let fee = self.config.fee(amount_owed)?;
+
+        if fee > 0 {
+            let cpi_program = self.token_program.to_account_info();
+
+            let cpi_accounts = TransferChecked {
+                from: self.taker_ata_b.to_account_info(),
+                to: self.treasury_ata_b.to_account_info(),
+                authority: self.taker.to_account_info(),
+                mint: self.mint_b.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+            transfer_checked(cpi_ctx, fee, self.mint_b.decimals)?;
+        }
+
         let cpi_program = self.token_program.to_account_info();
 
         let cpi_accounts = TransferChecked {
@@ -69,12 +151,13 @@ impl<'info> Take<'info> {
 
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
 
-        transfer_checked(cpi_ctx, self.escrow.receive, self.mint_b.decimals)
+        transfer_checked(cpi_ctx, amount_owed - fee, self.mint_b.decimals)
     }
 
     pub fn withdraw_and_close_vault(&mut self) -> Result<()> {
         let signer_seeds: [&[&[u8]]; 1] = [&[
             b"escrow",
+            &[ESCROW_VERSION],
             self.maker.key.as_ref(),
             &self.escrow.seed.to_le_bytes()[..],
             &[self.escrow.bump]
@@ -103,6 +186,12 @@ impl<'info> Take<'info> {
 
         let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
 
-        close_account(cpi_context)
+        close_account(cpi_context)?;
+
+        if let Some(order_book) = &self.order_book {
+            order_book.load_mut()?.remove(&self.escrow.key());
+        }
+
+        Ok(())
     }
 }
\ No newline at end of file
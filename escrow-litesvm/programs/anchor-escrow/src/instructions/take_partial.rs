@@ -0,0 +1,203 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked, CloseAccount, close_account}};
+
+use crate::{error::EscrowError, state::{Config, Escrow, OrderBook, ESCROW_VERSION}};
+
+//Same accounts as Take, the escrow is only closed once the offer is fully filled
+#[derive(Accounts)]
+pub struct TakePartial<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    pub mint_b: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+    )]
+    pub taker_ata_a: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+    )]
+    pub taker_ata_b: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+    )]
+    pub maker_ata_b: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = mint_a,
+        has_one = mint_b,
+        seeds = [b"escrow", [ESCROW_VERSION].as_ref(), maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    // offers made before order books existed can be settled without one
+    #[account(
+        mut,
+        seeds = [b"order_book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = order_book.load()?.bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ EscrowError::Paused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(address = config.treasury)]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+//Pay amount_b of mint_b to the maker
+//Pay the same share of the vault to the taker
+//Close vault and escrow once nothing is left to receive
+impl<'info> TakePartial<'info> {
+    pub fn fill(&mut self, amount_b: u64) -> Result<()> {
+        require!(!self.escrow.is_oracle_priced(), EscrowError::OraclePricedOffer);
+        require!(
+            !self.escrow.is_expired(Clock::get()?.unix_timestamp),
+            EscrowError::OfferExpired
+        );
+        require!(
+            amount_b > 0 && amount_b <= self.escrow.receive,
+            EscrowError::InvalidFillAmount
+        );
+
+        // vault.amount * amount_b / receive, the last fill takes whatever is left
+        let amount_a = u64::try_from(
+            (self.vault.amount as u128)
+                .checked_mul(amount_b as u128)
+                .ok_or(EscrowError::Overflow)?
+                / self.escrow.receive as u128,
+        )
+        .map_err(|_| EscrowError::Overflow)?;
+        require!(amount_a > 0, EscrowError::FillTooSmall);
+
+        self.deposit(amount_b)?;
+        self.withdraw(amount_a)?;
+
+        self.escrow.receive -= amount_b;
+        self.escrow.remaining_deposit = self.escrow.remaining_deposit.saturating_sub(amount_a);
+
+        if let Some(order_book) = &self.order_book {
+            let mut order_book = order_book.load_mut()?;
+            if self.escrow.receive == 0 {
+                order_book.remove(&self.escrow.key());
+            } else {
+                order_book.update(self.escrow.order_entry(self.escrow.key()));
+            }
+        }
+        if self.escrow.receive == 0 {
+            self.close_vault()?;
+            self.escrow.close(self.maker.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    fn deposit(&mut self, amount_b: u64) -> Result<()> {
+        // the venue fee comes out of what the maker receives
+        let fee = self.config.fee(amount_b)?;
+
+        if fee > 0 {
+            let cpi_program = self.token_program.to_account_info();
+
+            let cpi_accounts = TransferChecked {
+                from: self.taker_ata_b.to_account_info(),
+                to: self.treasury_ata_b.to_account_info(),
+                authority: self.taker.to_account_info(),
+                mint: self.mint_b.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+            transfer_checked(cpi_ctx, fee, self.mint_b.decimals)?;
+        }
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from: self.taker_ata_b.to_account_info(),
+            to: self.maker_ata_b.to_account_info(),
+            authority: self.taker.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer_checked(cpi_ctx, amount_b - fee, self.mint_b.decimals)
+    }
+
+    fn withdraw(&mut self, amount_a: u64) -> Result<()> {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            &[ESCROW_VERSION],
+            self.maker.key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump]
+        ]];
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            to: self.taker_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+
+        transfer_checked(cpi_context, amount_a, self.mint_a.decimals)?;
+
+        self.vault.reload()
+    }
+
+    fn close_vault(&mut self) -> Result<()> {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            &[ESCROW_VERSION],
+            self.maker.key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump]
+        ]];
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+
+        close_account(cpi_context)
+    }
+}
@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{error::EscrowError, state::{Escrow, OrderBook, ESCROW_VERSION}};
+
+#[derive(Accounts)]
+pub struct UpdateOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        has_one = mint_a,
+        has_one = maker,
+        seeds = [b"escrow", [ESCROW_VERSION].as_ref(), maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    // offers made before order books existed can be settled without one
+    #[account(
+        mut,
+        seeds = [b"order_book", escrow.mint_a.as_ref(), escrow.mint_b.as_ref()],
+        bump = order_book.load()?.bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+//Reprice the offer in place
+//Positive add_deposit tops up the vault, negative withdraws back to the maker
+impl<'info> UpdateOffer<'info> {
+    pub fn update_receive(&mut self, new_receive: u64) -> Result<()> {
+        require!(!self.escrow.is_oracle_priced(), EscrowError::OraclePricedOffer);
+        require!(new_receive > 0, EscrowError::InvalidReceive);
+
+        self.escrow.receive = new_receive;
+
+        Ok(())
+    }
+
+    pub fn change_deposit(&mut self, add_deposit: i64) -> Result<()> {
+        let amount = add_deposit.unsigned_abs();
+
+        if add_deposit > 0 {
+            let cpi_program = self.token_program.to_account_info();
+
+            let cpi_accounts = TransferChecked {
+                from: self.maker_ata_a.to_account_info(),
+                to: self.vault.to_account_info(),
+                authority: self.maker.to_account_info(),
+                mint: self.mint_a.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+            transfer_checked(cpi_ctx, amount, self.mint_a.decimals)?;
+        } else if add_deposit < 0 {
+            require!(amount < self.vault.amount, EscrowError::InvalidDepositChange);
+
+            let signer_seeds: [&[&[u8]]; 1] = [&[
+                b"escrow",
+                &[ESCROW_VERSION],
+                self.maker.key.as_ref(),
+                &self.escrow.seed.to_le_bytes()[..],
+                &[self.escrow.bump]
+            ]];
+
+            let cpi_program = self.token_program.to_account_info();
+
+            let cpi_accounts = TransferChecked {
+                from: self.vault.to_account_info(),
+                to: self.maker_ata_a.to_account_info(),
+                authority: self.escrow.to_account_info(),
+                mint: self.mint_a.to_account_info(),
+            };
+
+            let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, &signer_seeds);
+
+            transfer_checked(cpi_context, amount, self.mint_a.decimals)?;
+        }
+
+        self.vault.reload()?;
+        self.escrow.remaining_deposit = self.vault.amount;
+
+        // the new price may move the offer within the book
+        if let Some(order_book) = &self.order_book {
+            order_book.load_mut()?.update(self.escrow.order_entry(self.escrow.key()));
+        }
+
+        Ok(())
+    }
+}
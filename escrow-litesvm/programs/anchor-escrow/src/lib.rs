@@ -5,6 +5,7 @@ use anchor_lang::prelude::*;
 
 mod state;
 mod instructions;
+mod error;
 mod tests;
 
 use instructions::*;
@@ -15,17 +16,72 @@ declare_id!("FircrADQ2wgGuvpm8qneNCfKM7o5zoHTWnDQxngpTQ3J");
 pub mod anchor_escrow {
     use super::*;
 
-    pub fn make(ctx: Context<Make>, seed: u64, deposit: u64, receive: u64) -> Result<()> {
-        ctx.accounts.init_escrow(seed, receive, &ctx.bumps)?;
-        ctx.accounts.deposit(deposit)
+    pub fn initialize_config(ctx: Context<InitializeConfig>, fee_bps: u16, treasury: Pubkey) -> Result<()> {
+        ctx.accounts.init_config(fee_bps, treasury, &ctx.bumps)
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, fee_bps: Option<u16>, treasury: Option<Pubkey>, paused: Option<bool>) -> Result<()> {
+        ctx.accounts.update_config(fee_bps, treasury, paused)
+    }
+
+    pub fn make(ctx: Context<Make>, seed: u64, deposit: u64, receive: u64, expiry: i64) -> Result<()> {
+        ctx.accounts.init_escrow(seed, receive, expiry, &ctx.bumps)?;
+        ctx.accounts.deposit(deposit)?;
+        ctx.accounts.list(&ctx.bumps)
+    }
+
+    pub fn make_oracle(ctx: Context<Make>, seed: u64, deposit: u64, price_feed: Pubkey, spread_bps: i16, expiry: i64) -> Result<()> {
+        ctx.accounts.init_oracle_escrow(seed, price_feed, spread_bps, expiry, &ctx.bumps)?;
+        ctx.accounts.deposit(deposit)?;
+        ctx.accounts.list(&ctx.bumps)
     }
 
     pub fn refund(ctx: Context<Refund>) -> Result<()> {
         ctx.accounts.refund_and_close_vault()
     }
 
-    pub fn take(ctx: Context<Take>) -> Result<()> {
-        ctx.accounts.deposit()?;
+    pub fn refund_legacy(ctx: Context<RefundLegacy>) -> Result<()> {
+        ctx.accounts.refund_and_close_vault()
+    }
+
+    pub fn take(ctx: Context<Take>, expected_receive: Option<u64>) -> Result<()> {
+        ctx.accounts.check_not_expired()?;
+        let amount_owed = ctx.accounts.amount_owed()?;
+        ctx.accounts.check_price(expected_receive, amount_owed)?;
+        ctx.accounts.deposit(amount_owed)?;
+        ctx.accounts.withdraw_and_close_vault()
+    }
+
+    pub fn take_best(ctx: Context<Take>, max_receive: u64) -> Result<()> {
+        ctx.accounts.check_not_expired()?;
+        let amount_owed = ctx.accounts.amount_owed()?;
+        ctx.accounts.check_best(max_receive, amount_owed)?;
+        ctx.accounts.deposit(amount_owed)?;
         ctx.accounts.withdraw_and_close_vault()
     }
+
+    pub fn take_partial(ctx: Context<TakePartial>, amount_b: u64) -> Result<()> {
+        ctx.accounts.fill(amount_b)
+    }
+
+    pub fn match_offers(ctx: Context<MatchOffers>, matching_fee: u64) -> Result<()> {
+        let (amount_a, owed_to_x) = ctx.accounts.crossing(matching_fee)?;
+        ctx.accounts.swap(amount_a, owed_to_x, matching_fee)?;
+        ctx.accounts.settle_x(owed_to_x)?;
+        ctx.accounts.settle_y(amount_a)
+    }
+
+    pub fn update_offer(ctx: Context<UpdateOffer>, new_receive: u64, add_deposit: i64) -> Result<()> {
+        ctx.accounts.update_receive(new_receive)?;
+        ctx.accounts.change_deposit(add_deposit)
+    }
+
+    pub fn close_expired(ctx: Context<CloseExpired>) -> Result<()> {
+        ctx.accounts.pay_bounty()?;
+        ctx.accounts.return_and_close_vault()
+    }
+
+    pub fn init_order_book(ctx: Context<InitOrderBook>) -> Result<()> {
+        ctx.accounts.init_order_book(&ctx.bumps)
+    }
 }
\ No newline at end of file
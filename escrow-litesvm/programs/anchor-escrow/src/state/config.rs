@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::error::EscrowError;
+
+pub const MAX_FEE_BPS: u16 = 10_000;
+
+#[account]
+#[derive(InitSpace, Debug)]
+pub struct Config {
+    pub admin: Pubkey,
+    pub fee_bps: u16,
+    pub treasury: Pubkey,
+    pub paused: bool,
+    pub bump: u8,
+}
+
+impl Config {
+    // taker fee on `amount` of mint_b, rounded down
+    pub fn fee(&self, amount: u64) -> Result<u64> {
+        let fee = (amount as u128)
+            .checked_mul(self.fee_bps as u128)
+            .ok_or(EscrowError::Overflow)?
+            / MAX_FEE_BPS as u128;
+        Ok(fee as u64)
+    }
+}
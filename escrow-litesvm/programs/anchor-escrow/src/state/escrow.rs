@@ -1,12 +1,110 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, Discriminator};
+
+use crate::{error::EscrowError, state::{OrderEntry, PriceFeed}};
+
+// part of the escrow seeds, bumped with every change to the Escrow layout so accounts in
+// an older layout never sit where the current one is expected
+pub const ESCROW_VERSION: u8 = 2;
+
+pub const MAX_SPREAD_BPS: i16 = 5_000;
+// seconds a price stays usable after it was published
+pub const MAX_PRICE_AGE: i64 = 60;
 
 #[account]
 #[derive(InitSpace, Debug)]
 pub struct Escrow {
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub receive: u64, // unused for oracle-priced offers
+    pub remaining_deposit: u64,
+    pub expiry: i64, // 0 when the offer never expires
+    pub price_feed: Pubkey, // default for fixed-price offers
+    pub spread_bps: i16, // added to the oracle price, negative for a discount
+    pub bump: u8,
+}
+
+impl Escrow {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expiry != 0 && now >= self.expiry
+    }
+
+    pub fn is_oracle_priced(&self) -> bool {
+        self.price_feed != Pubkey::default()
+    }
+
+    pub fn order_entry(&self, escrow: Pubkey) -> OrderEntry {
+        OrderEntry {
+            escrow,
+            deposit: self.remaining_deposit,
+            receive: self.receive,
+        }
+    }
+
+    // deposit * price * 10^expo * (1 + spread), rounded up in the maker's favour
+    pub fn oracle_amount_owed(&self, deposit: u64, feed: &PriceFeed, now: i64) -> Result<u64> {
+        require!(feed.price > 0, EscrowError::InvalidPrice);
+        require!(
+            now.saturating_sub(feed.publish_time) <= MAX_PRICE_AGE,
+            EscrowError::StalePrice
+        );
+
+        let scale = 10u128
+            .checked_pow(feed.expo.unsigned_abs())
+            .ok_or(EscrowError::Overflow)?;
+        let mut numerator = (deposit as u128)
+            .checked_mul(feed.price as u128)
+            .and_then(|n| n.checked_mul((10_000 + self.spread_bps as i32) as u128))
+            .ok_or(EscrowError::Overflow)?;
+        let mut denominator = 10_000u128;
+        if feed.expo >= 0 {
+            numerator = numerator.checked_mul(scale).ok_or(EscrowError::Overflow)?;
+        } else {
+            denominator = denominator.checked_mul(scale).ok_or(EscrowError::Overflow)?;
+        }
+
+        let owed = u64::try_from(numerator.div_ceil(denominator))
+            .map_err(|_| EscrowError::Overflow)?;
+        require!(owed > 0, EscrowError::InvalidPrice);
+
+        Ok(owed)
+    }
+}
+
+// Escrow as made before ESCROW_VERSION, at [b"escrow", maker, seed], only refund_legacy reads it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LegacyEscrow {
     pub seed: u64,
     pub maker: Pubkey,
     pub mint_a: Pubkey,
     pub mint_b: Pubkey,
     pub receive: u64,
     pub bump: u8,
-}
\ No newline at end of file
+}
+
+impl Discriminator for LegacyEscrow {
+    // the layout changed, the account name did not
+    const DISCRIMINATOR: &'static [u8] = Escrow::DISCRIMINATOR;
+}
+
+impl Owner for LegacyEscrow {
+    fn owner() -> Pubkey {
+        crate::ID
+    }
+}
+
+// legacy escrows are only ever closed, never written
+impl AccountSerialize for LegacyEscrow {}
+
+impl AccountDeserialize for LegacyEscrow {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        require!(buf.starts_with(Self::DISCRIMINATOR), ErrorCode::AccountDiscriminatorMismatch);
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        let mut data = buf.get(Self::DISCRIMINATOR.len()..).ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        AnchorDeserialize::deserialize(&mut data).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+}
@@ -1,3 +1,9 @@
+pub mod config;
 pub mod escrow;
+pub mod order_book;
+pub mod price_feed;
 
-pub use escrow::*;
\ No newline at end of file
+pub use config::*;
+pub use escrow::*;
+pub use order_book::*;
+pub use price_feed::*;
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+// open fixed-price offers kept per pair, the worst-priced ones drop out of a full book
+pub const MAX_ORDERS: usize = 32;
+
+#[zero_copy]
+#[derive(Debug, Default)]
+pub struct OrderEntry {
+    pub escrow: Pubkey,
+    pub deposit: u64, // mint_a left in the vault
+    pub receive: u64, // mint_b asked for it
+}
+
+impl OrderEntry {
+    // cheaper for the taker, less mint_b per mint_a
+    pub fn is_better_than(&self, other: &OrderEntry) -> bool {
+        (self.receive as u128) * (other.deposit as u128) < (other.receive as u128) * (self.deposit as u128)
+    }
+}
+
+// Open fixed-price offers for one (mint_a, mint_b) pair, sorted best price first
+#[account(zero_copy)]
+#[derive(Debug)]
+pub struct OrderBook {
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub len: u32,
+    pub bump: u8,
+    pub _padding: [u8; 3],
+    pub entries: [OrderEntry; MAX_ORDERS],
+}
+
+impl OrderBook {
+    pub const SPACE: usize = 8 + std::mem::size_of::<OrderBook>();
+
+    pub fn orders(&self) -> &[OrderEntry] {
+        &self.entries[..self.len as usize]
+    }
+
+    pub fn best(&self) -> Option<&OrderEntry> {
+        self.orders().first()
+    }
+
+    // equal prices keep listing order, a full book evicts its worst entry for a better one
+    // and leaves a worse one unlisted, the offer itself stays open either way
+    pub fn insert(&mut self, entry: OrderEntry) {
+        let len = self.len as usize;
+        let index = self.orders()
+            .iter()
+            .position(|listed| entry.is_better_than(listed))
+            .unwrap_or(len);
+        if index == MAX_ORDERS {
+            return;
+        }
+
+        let kept = len.min(MAX_ORDERS - 1);
+        self.entries.copy_within(index..kept, index + 1);
+        self.entries[index] = entry;
+        self.len = kept as u32 + 1;
+    }
+
+    // false when the escrow was not listed
+    pub fn remove(&mut self, escrow: &Pubkey) -> bool {
+        let len = self.len as usize;
+        let Some(index) = self.orders().iter().position(|listed| listed.escrow == *escrow) else {
+            return false;
+        };
+        self.entries.copy_within(index + 1..len, index);
+        self.entries[len - 1] = OrderEntry::default();
+        self.len -= 1;
+        true
+    }
+
+    // unlisted offers stay unlisted
+    pub fn update(&mut self, entry: OrderEntry) {
+        if self.remove(&entry.escrow) {
+            self.insert(entry);
+        }
+    }
+}
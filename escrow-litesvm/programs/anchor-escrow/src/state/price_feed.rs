@@ -0,0 +1,40 @@
+use anchor_lang::{prelude::*, Discriminator};
+
+// the only program trusted to publish prices for oracle-priced offers
+pub const ORACLE_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("zL6AYha7is9BdNNBuhnoVDTQzfYEYSdSgFWZtSbvkHY");
+
+// Mirror of the oracle's price account, read here so the escrow does not link the oracle crate,
+// price * 10^expo units of the quote mint per base unit of the priced mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PriceFeed {
+    pub authority: Pubkey,
+    pub price: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+impl Discriminator for PriceFeed {
+    // sha256("account:PriceFeed")[..8], same as the oracle's #[account]
+    const DISCRIMINATOR: &'static [u8] = &[189, 103, 252, 23, 152, 35, 243, 156];
+}
+
+impl Owner for PriceFeed {
+    fn owner() -> Pubkey {
+        ORACLE_PROGRAM_ID
+    }
+}
+
+// read-only, the escrow never writes price accounts
+impl AccountSerialize for PriceFeed {}
+
+impl AccountDeserialize for PriceFeed {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        require!(buf.starts_with(Self::DISCRIMINATOR), ErrorCode::AccountDiscriminatorMismatch);
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        let mut data = buf.get(Self::DISCRIMINATOR.len()..).ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        AnchorDeserialize::deserialize(&mut data).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+}
@@ -0,0 +1,167 @@
+//! Account snapshots for the LiteSVM tests.
+//!
+//! Tests never touch the network: they load accounts from `fixtures/<address>.bin`.
+//! To refresh or add snapshots, run the opt-in dump against an RPC node:
+//!
+//! ```text
+//! SNAPSHOT_ACCOUNTS=<address>,<address> SNAPSHOT_RPC_URL=https://api.devnet.solana.com \
+//!     cargo test dump_fixtures -- --ignored
+//! ```
+//!
+//! and commit the files it writes. `SNAPSHOT_ACCOUNTS` defaults to `DEFAULT_ACCOUNTS`
+//! and `SNAPSHOT_RPC_URL` to devnet. Every address in `DEFAULT_ACCOUNTS` is loaded by
+//! `load_default_fixtures`, so add it there once its file is committed.
+//!
+//! Layout of a fixture file, integers little endian:
+//! `b"ACCT"` | version: u8 | lamports: u64 | rent_epoch: u64 | executable: u8 |
+//! owner: [u8; 32] | data_len: u64 | data
+
+use {
+    litesvm::LiteSVM,
+    solana_account::Account,
+    solana_pubkey::Pubkey,
+    std::path::PathBuf,
+};
+
+const MAGIC: &[u8; 4] = b"ACCT";
+const VERSION: u8 = 1;
+
+// Accounts every test starts with, each needs a committed fixture. The devnet wallet the
+// tests used to fetch only funded the payer, which an airdrop does just as well.
+pub const DEFAULT_ACCOUNTS: &[Pubkey] = &[];
+
+pub fn fixture_path(address: &Pubkey) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(format!("{}.bin", address))
+}
+
+pub fn encode(account: &Account) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(62 + account.data.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(VERSION);
+    bytes.extend_from_slice(&account.lamports.to_le_bytes());
+    bytes.extend_from_slice(&account.rent_epoch.to_le_bytes());
+    bytes.push(account.executable as u8);
+    bytes.extend_from_slice(account.owner.as_ref());
+    bytes.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&account.data);
+    bytes
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Account, String> {
+    fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+        if bytes.len() < len {
+            return Err("fixture is truncated".to_string());
+        }
+        let (head, tail) = bytes.split_at(len);
+        *bytes = tail;
+        Ok(head)
+    }
+    let u64_from = |b: &[u8]| u64::from_le_bytes(b.try_into().unwrap());
+
+    let mut bytes = bytes;
+    if take(&mut bytes, 4)? != MAGIC {
+        return Err("not an account fixture".to_string());
+    }
+    let version = take(&mut bytes, 1)?[0];
+    if version != VERSION {
+        return Err(format!("unsupported fixture version {}", version));
+    }
+    let lamports = u64_from(take(&mut bytes, 8)?);
+    let rent_epoch = u64_from(take(&mut bytes, 8)?);
+    let executable = take(&mut bytes, 1)?[0] != 0;
+    let owner = Pubkey::try_from(take(&mut bytes, 32)?).unwrap();
+    let data_len = u64_from(take(&mut bytes, 8)?) as usize;
+    let data = take(&mut bytes, data_len)?.to_vec();
+    if !bytes.is_empty() {
+        return Err("trailing bytes after account data".to_string());
+    }
+
+    Ok(Account { lamports, data, owner, executable, rent_epoch })
+}
+
+// Panics when the snapshot is missing, a test silently running without its accounts
+// is worse than one that fails
+pub fn read_fixture(address: &Pubkey) -> Account {
+    let path = fixture_path(address);
+    let bytes = std::fs::read(&path).unwrap_or_else(|e| {
+        panic!(
+            "missing fixture {} ({}), dump it with `cargo test dump_fixtures -- --ignored`",
+            path.display(),
+            e
+        )
+    });
+    decode(&bytes).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+pub fn load_fixture(svm: &mut LiteSVM, address: &Pubkey) -> Account {
+    let account = read_fixture(address);
+    svm.set_account(*address, account.clone()).unwrap();
+    account
+}
+
+pub fn load_default_fixtures(svm: &mut LiteSVM) {
+    for address in DEFAULT_ACCOUNTS {
+        load_fixture(svm, address);
+    }
+}
+
+#[test]
+fn test_fixture_roundtrip() {
+    let account = Account {
+        lamports: 42,
+        data: vec![1, 2, 3],
+        owner: Pubkey::new_unique(),
+        executable: true,
+        rent_epoch: u64::MAX,
+    };
+    assert_eq!(decode(&encode(&account)).unwrap(), account);
+    assert!(decode(&encode(&account)[..20]).is_err());
+}
+
+#[test]
+fn test_default_fixtures_committed() {
+    for address in DEFAULT_ACCOUNTS {
+        assert!(fixture_path(address).exists(), "missing fixture for {}", address);
+    }
+}
+
+#[test]
+#[ignore = "needs network access, run with --ignored to refresh the fixtures"]
+fn dump_fixtures() {
+    use {
+        solana_address::Address,
+        solana_rpc_client::rpc_client::RpcClient,
+        std::str::FromStr,
+    };
+
+    let rpc_url = std::env::var("SNAPSHOT_RPC_URL")
+        .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string());
+    let accounts: Vec<Pubkey> = match std::env::var("SNAPSHOT_ACCOUNTS") {
+        Ok(list) => list
+            .split(',')
+            .map(|address| Pubkey::from_str(address.trim()).expect("invalid address"))
+            .collect(),
+        Err(_) => DEFAULT_ACCOUNTS.to_vec(),
+    };
+
+    let rpc_client = RpcClient::new(rpc_url);
+    for address in accounts {
+        let fetched_account = rpc_client
+            .get_account(&Address::from(address.to_bytes()))
+            .unwrap_or_else(|e| panic!("Failed to fetch {}: {}", address, e));
+        let account = Account {
+            lamports: fetched_account.lamports,
+            data: fetched_account.data,
+            owner: Pubkey::from(fetched_account.owner.to_bytes()),
+            executable: fetched_account.executable,
+            rent_epoch: fetched_account.rent_epoch,
+        };
+
+        let path = fixture_path(&address);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, encode(&account)).unwrap();
+        println!("wrote {}", path.display());
+    }
+}
@@ -1,3 +1,6 @@
+#[cfg(test)]
+mod fixtures;
+
 #[cfg(test)]
 mod tests {
 
@@ -6,6 +9,8 @@ mod tests {
             prelude::msg, 
             solana_program::program_pack::Pack, 
             AccountDeserialize, 
+            AccountSerialize, 
+            Discriminator, 
             InstructionData, 
             ToAccountMetas
         }, anchor_spl::{
@@ -21,26 +26,80 @@ mod tests {
             CreateAssociatedTokenAccount, 
             CreateMint, MintTo
         }, 
-        solana_rpc_client::rpc_client::RpcClient,
         solana_account::Account,
         solana_instruction::Instruction, 
         solana_keypair::Keypair, 
         solana_message::Message, 
         solana_native_token::LAMPORTS_PER_SOL, 
         solana_pubkey::Pubkey, 
-        solana_sdk_ids::system_program::ID as SYSTEM_PROGRAM_ID, 
+        solana_sdk_ids::{bpf_loader_upgradeable::ID as BPF_LOADER_UPGRADEABLE_ID, system_program::ID as SYSTEM_PROGRAM_ID}, 
         solana_signer::Signer, 
         solana_transaction::Transaction, 
-        solana_address::Address, 
-        std::{
-            path::PathBuf, 
-            str::FromStr
-        }
+        std::path::PathBuf,
+        super::fixtures,
     };
 
     static PROGRAM_ID: Pubkey = crate::ID;
+    static TREASURY: Pubkey = Pubkey::new_from_array([7; 32]);
 
-    fn setup() -> (LiteSVM, Keypair) {
+    fn config_pda() -> Pubkey {
+        Pubkey::find_program_address(&[b"config"], &PROGRAM_ID).0
+    }
+
+    fn order_book_pda(mint_a: &Pubkey, mint_b: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"order_book", mint_a.as_ref(), mint_b.as_ref()], &PROGRAM_ID).0
+    }
+
+    fn program_data_pda() -> Pubkey {
+        Pubkey::find_program_address(&[PROGRAM_ID.as_ref()], &BPF_LOADER_UPGRADEABLE_ID).0
+    }
+
+    // Helper: Deploy `elf` behind the upgradeable loader so the program has a ProgramData account
+    fn add_upgradeable_program(program: &mut LiteSVM, program_id: Pubkey, upgrade_authority: &Pubkey, elf: &[u8]) {
+        let program_data = program_data_pda();
+
+        // UpgradeableLoaderState::ProgramData { slot, upgrade_authority_address } followed by the ELF
+        let mut data = vec![3, 0, 0, 0];
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(upgrade_authority.as_ref());
+        data.extend_from_slice(elf);
+        program.set_account(program_data, Account {
+            lamports: program.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: BPF_LOADER_UPGRADEABLE_ID,
+            executable: false,
+            rent_epoch: 0,
+        }).unwrap();
+
+        // UpgradeableLoaderState::Program { programdata_address }
+        let mut data = vec![2, 0, 0, 0];
+        data.extend_from_slice(program_data.as_ref());
+        program.set_account(program_id, Account {
+            lamports: program.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: BPF_LOADER_UPGRADEABLE_ID,
+            executable: true,
+            rent_epoch: 0,
+        }).unwrap();
+    }
+
+    fn initialize_config_ix(admin: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::InitializeConfig {
+                admin: *admin,
+                program: PROGRAM_ID,
+                program_data: program_data_pda(),
+                config: config_pda(),
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::InitializeConfig { fee_bps: 0, treasury: TREASURY }.data(),
+        }
+    }
+
+    // Helper: Deploy the program with the payer as upgrade authority, without a config
+    fn deploy() -> (LiteSVM, Keypair) {
         // Initialize LiteSVM and payer
         let mut program = LiteSVM::new();
         let payer = Keypair::new();
@@ -56,29 +115,43 @@ mod tests {
     
         let program_data = std::fs::read(so_path).expect("Failed to read program SO file");
     
-        program.add_program(PROGRAM_ID, &program_data);
-
-        // Example on how to Load an account from devnet
-        let rpc_client = RpcClient::new("https://api.devnet.solana.com");
-        let account_address = Address::from_str("DRYvf71cbF2s5wgaJQvAGkghMkRcp5arvsK2w97vXhi2").unwrap();
-        let fetched_account = rpc_client
-            .get_account(&account_address)
-            .expect("Failed to fetch account from devnet");
-
-        program.set_account(payer.pubkey(), Account { 
-            lamports: fetched_account.lamports, 
-            data: fetched_account.data, 
-            owner: Pubkey::from(fetched_account.owner.to_bytes()), 
-            executable: fetched_account.executable, 
-            rent_epoch: fetched_account.rent_epoch 
-        }).unwrap();
+        add_upgradeable_program(&mut program, PROGRAM_ID, &payer.pubkey(), &program_data);
+
+        // Account snapshots shared by every test, see `fixtures.rs` to dump more from devnet
+        fixtures::load_default_fixtures(&mut program);
+
+        (program, payer)
+    }
+
+    fn setup() -> (LiteSVM, Keypair) {
+        let (mut program, payer) = deploy();
 
-        msg!("Lamports of fetched account: {}", fetched_account.lamports);
+        // The payer administers a fee-free venue
+        let config_ix = initialize_config_ix(&payer.pubkey());
+        send_ix(&mut program, config_ix, &[&payer]).expect("Failed to initialize config");
     
         // Return the LiteSVM instance and payer keypair
         (program, payer)
     }
 
+    #[test]
+    fn test_initialize_config() {
+        let (mut program, payer) = deploy();
+
+        // only the upgrade authority can claim the admin role
+        let stranger = Keypair::new();
+        program.airdrop(&stranger.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        let ix = initialize_config_ix(&stranger.pubkey());
+        assert_escrow_error(send_ix(&mut program, ix, &[&stranger]), crate::error::EscrowError::NotUpgradeAuthority);
+
+        let ix = initialize_config_ix(&payer.pubkey());
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+
+        let config = program.get_account(&config_pda()).unwrap();
+        let config = crate::state::Config::try_deserialize(&mut config.data.as_ref()).unwrap();
+        assert_eq!(config.admin, payer.pubkey());
+    }
+
     #[test]
     fn test_make() {
 
@@ -110,7 +183,7 @@ mod tests {
 
         // Derive the PDA for the escrow account using the maker's public key and a seed value
         let escrow = Pubkey::find_program_address(
-            &[b"escrow", maker.as_ref(), &123u64.to_le_bytes()],
+            &[b"escrow", &[crate::state::ESCROW_VERSION], maker.as_ref(), &123u64.to_le_bytes()],
             &PROGRAM_ID
         ).0;
         msg!("Escrow PDA: {}\n", escrow);
@@ -139,11 +212,13 @@ mod tests {
                 maker_ata_a: maker_ata_a,
                 escrow: escrow,
                 vault: vault,
+                order_book: order_book_pda(&mint_a, &mint_b),
+                config: config_pda(),
                 associated_token_program: asspciated_token_program,
                 token_program: token_program,
                 system_program: system_program,
             }.to_account_metas(None),
-            data: crate::instruction::Make {deposit: 10, seed: 123u64, receive: 10 }.data(),
+            data: crate::instruction::Make {deposit: 10, seed: 123u64, receive: 10, expiry: 0 }.data(),
         };
 
         // Create and send the transaction containing the "Make" instruction
@@ -174,7 +249,992 @@ mod tests {
         assert_eq!(escrow_data.mint_a, mint_a);
         assert_eq!(escrow_data.mint_b, mint_b);
         assert_eq!(escrow_data.receive, 10);
+        assert_eq!(escrow_data.remaining_deposit, 10);
+        assert_eq!(escrow_data.expiry, 0);
         
     }
 
-}
\ No newline at end of file
+    // Helper: Sign and send a single instruction, the first signer pays the fees
+    fn send_ix(program: &mut LiteSVM, ix: Instruction, signers: &[&Keypair]) -> litesvm::types::TransactionResult {
+        let message = Message::new(&[ix], Some(&signers[0].pubkey()));
+        let recent_blockhash = program.latest_blockhash();
+        let transaction = Transaction::new(signers, message, recent_blockhash);
+        program.send_transaction(transaction)
+    }
+
+    // Works for legacy and Token-2022 accounts alike
+    fn token_balance(program: &LiteSVM, account: &Pubkey) -> u64 {
+        let account = program.get_account(account).unwrap();
+        spl_token_2022::extension::StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+            .unwrap()
+            .base
+            .amount
+    }
+
+    fn is_closed(program: &LiteSVM, account: &Pubkey) -> bool {
+        program.get_account(account).map_or(true, |account| account.lamports == 0)
+    }
+
+    fn ata(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+        associated_token::get_associated_token_address_with_program_id(owner, mint, token_program)
+    }
+
+    fn create_mint(program: &mut LiteSVM, payer: &Keypair, token_program: &Pubkey) -> Pubkey {
+        CreateMint::new(program, payer)
+            .decimals(6)
+            .authority(&payer.pubkey())
+            .token_program_id(token_program)
+            .send()
+            .unwrap()
+    }
+
+    // Helper: Create `owner`'s ATA and mint it 1,000 tokens
+    fn create_funded_ata(program: &mut LiteSVM, payer: &Keypair, owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+        let ata = CreateAssociatedTokenAccount::new(program, payer, mint)
+            .owner(owner)
+            .token_program_id(token_program)
+            .send()
+            .unwrap();
+        MintTo::new(program, payer, mint, &ata, 1000000000)
+            .token_program_id(token_program)
+            .send()
+            .unwrap();
+        ata
+    }
+
+    // Helper: Assert the transaction failed with the given escrow error
+    fn assert_escrow_error(result: litesvm::types::TransactionResult, expected: crate::error::EscrowError) {
+        let err = result.expect_err("Transaction should have failed");
+        assert_eq!(
+            format!("{:?}", err.err),
+            format!("InstructionError(0, Custom({}))", u32::from(expected)),
+            "logs: {:#?}",
+            err.meta.logs
+        );
+    }
+
+    // Helper: Move the clock to `unix_timestamp`
+    fn warp_to(program: &mut LiteSVM, unix_timestamp: i64) {
+        let mut clock = program.get_sysvar::<anchor_lang::prelude::Clock>();
+        clock.unix_timestamp = unix_timestamp;
+        program.set_sysvar(&clock);
+        program.expire_blockhash();
+    }
+
+    // Helper: Create both mints and an offer of `deposit` A for `receive` B made by the payer
+    fn make_offer(program: &mut LiteSVM, payer: &Keypair, seed: u64, deposit: u64, receive: u64, expiry: i64, token_program: &Pubkey) -> (Pubkey, Pubkey, Pubkey, Pubkey) {
+        let maker = payer.pubkey();
+        let mint_a = create_mint(program, payer, token_program);
+        let mint_b = create_mint(program, payer, token_program);
+        create_funded_ata(program, payer, &maker, &mint_a, token_program);
+        let escrow = escrow_pda(&maker, seed);
+        let vault = ata(&escrow, &mint_a, token_program);
+
+        let ix = make_ix(&maker, &mint_a, &mint_b, seed, deposit, receive, expiry, token_program);
+        send_ix(program, ix, &[payer]).unwrap();
+
+        (mint_a, mint_b, escrow, vault)
+    }
+
+    fn escrow_pda(maker: &Pubkey, seed: u64) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"escrow", &[crate::state::ESCROW_VERSION], maker.as_ref(), &seed.to_le_bytes()],
+            &PROGRAM_ID
+        ).0
+    }
+
+    fn make_ix(maker: &Pubkey, mint_a: &Pubkey, mint_b: &Pubkey, seed: u64, deposit: u64, receive: u64, expiry: i64, token_program: &Pubkey) -> Instruction {
+        let escrow = escrow_pda(maker, seed);
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Make {
+                maker: *maker,
+                mint_a: *mint_a,
+                mint_b: *mint_b,
+                maker_ata_a: ata(maker, mint_a, token_program),
+                escrow,
+                vault: ata(&escrow, mint_a, token_program),
+                config: config_pda(),
+                order_book: order_book_pda(mint_a, mint_b),
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: *token_program,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::Make { seed, deposit, receive, expiry }.data(),
+        }
+    }
+
+    // Helper: Fund a taker with 1,000 tokens of Mint B
+    fn setup_taker(program: &mut LiteSVM, payer: &Keypair, mint_b: &Pubkey, token_program: &Pubkey) -> (Keypair, Pubkey) {
+        let taker = Keypair::new();
+        program.airdrop(&taker.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        let taker_ata_b = create_funded_ata(program, payer, &taker.pubkey(), mint_b, token_program);
+        (taker, taker_ata_b)
+    }
+
+    fn take_partial_ix(taker: &Pubkey, maker: &Pubkey, mint_a: &Pubkey, mint_b: &Pubkey, escrow: &Pubkey, vault: &Pubkey, amount_b: u64) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::TakePartial {
+                taker: *taker,
+                maker: *maker,
+                mint_a: *mint_a,
+                mint_b: *mint_b,
+                taker_ata_a: associated_token::get_associated_token_address(taker, mint_a),
+                taker_ata_b: associated_token::get_associated_token_address(taker, mint_b),
+                maker_ata_b: associated_token::get_associated_token_address(maker, mint_b),
+                escrow: *escrow,
+                vault: *vault,
+                order_book: Some(order_book_pda(mint_a, mint_b)),
+                config: config_pda(),
+                treasury: TREASURY,
+                treasury_ata_b: associated_token::get_associated_token_address(&TREASURY, mint_b),
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::TakePartial { amount_b }.data(),
+        }
+    }
+
+    #[test]
+    fn test_take_partial() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+
+        // 100 A for 50 B
+        let (mint_a, mint_b, escrow, vault) = make_offer(&mut program, &payer, 7, 100, 50, 0, &TOKEN_PROGRAM_ID);
+        let (taker, taker_ata_b) = setup_taker(&mut program, &payer, &mint_b, &TOKEN_PROGRAM_ID);
+        let taker_ata_a = associated_token::get_associated_token_address(&taker.pubkey(), &mint_a);
+        let maker_ata_b = associated_token::get_associated_token_address(&maker, &mint_b);
+
+        // (amount_b, vault left, receive left, taker A total)
+        let fills = [(10, 80, 40, 20), (15, 50, 25, 50), (5, 40, 20, 60)];
+        for (amount_b, vault_left, receive_left, taker_a) in fills {
+            let ix = take_partial_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, amount_b);
+            send_ix(&mut program, ix, &[&taker]).unwrap();
+
+            assert_eq!(token_balance(&program, &vault), vault_left);
+            assert_eq!(token_balance(&program, &taker_ata_a), taker_a);
+            let escrow_data = crate::state::Escrow::try_deserialize(
+                &mut program.get_account(&escrow).unwrap().data.as_ref()
+            ).unwrap();
+            assert_eq!(escrow_data.receive, receive_left);
+            assert_eq!(escrow_data.remaining_deposit, vault_left);
+        }
+
+        // asking for more than is left fails
+        let ix = take_partial_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, 21);
+        assert_escrow_error(send_ix(&mut program, ix, &[&taker]), crate::error::EscrowError::InvalidFillAmount);
+
+        // the last fill empties the vault and closes the offer
+        let ix = take_partial_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, 20);
+        send_ix(&mut program, ix, &[&taker]).unwrap();
+
+        assert_eq!(token_balance(&program, &taker_ata_a), 100);
+        assert_eq!(token_balance(&program, &maker_ata_b), 50);
+        assert_eq!(token_balance(&program, &taker_ata_b), 1000000000 - 50);
+        assert!(is_closed(&program, &vault));
+        assert!(is_closed(&program, &escrow));
+    }
+
+    fn close_expired_ix(caller: &Pubkey, maker: &Pubkey, mint_a: &Pubkey, mint_b: &Pubkey, escrow: &Pubkey, vault: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::CloseExpired {
+                caller: *caller,
+                maker: *maker,
+                mint_a: *mint_a,
+                maker_ata_a: associated_token::get_associated_token_address(maker, mint_a),
+                escrow: *escrow,
+                vault: *vault,
+                order_book: Some(order_book_pda(mint_a, mint_b)),
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::CloseExpired {}.data(),
+        }
+    }
+
+    #[test]
+    fn test_close_expired() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+        let start = 1_700_000_000;
+        warp_to(&mut program, start);
+
+        let (mint_a, mint_b, escrow, vault) = make_offer(&mut program, &payer, 8, 100, 50, start + 100, &TOKEN_PROGRAM_ID);
+        let (taker, _) = setup_taker(&mut program, &payer, &mint_b, &TOKEN_PROGRAM_ID);
+        let cleaner = Keypair::new();
+        program.airdrop(&cleaner.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        let maker_ata_a = associated_token::get_associated_token_address(&maker, &mint_a);
+
+        // too early to clean up, still takeable
+        let ix = close_expired_ix(&cleaner.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault);
+        assert_escrow_error(send_ix(&mut program, ix, &[&cleaner]), crate::error::EscrowError::OfferNotExpired);
+        let ix = take_partial_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, 10);
+        send_ix(&mut program, ix, &[&taker]).unwrap();
+
+        // expired offers can not be taken
+        warp_to(&mut program, start + 100);
+        let ix = take_partial_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, 10);
+        assert_escrow_error(send_ix(&mut program, ix, &[&taker]), crate::error::EscrowError::OfferExpired);
+
+        // anyone can return what is left to the maker and collect the bounty
+        let escrow_rent = program.get_balance(&escrow).unwrap();
+        let vault_rent = program.get_balance(&vault).unwrap();
+        let maker_lamports = program.get_balance(&maker).unwrap();
+        let cleaner_lamports = program.get_balance(&cleaner.pubkey()).unwrap();
+        let maker_a = token_balance(&program, &maker_ata_a);
+
+        let ix = close_expired_ix(&cleaner.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault);
+        let tx = send_ix(&mut program, ix, &[&cleaner]).unwrap();
+        msg!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        let bounty = crate::instructions::CLOSE_EXPIRED_BOUNTY;
+        assert_eq!(token_balance(&program, &maker_ata_a), maker_a + 80);
+        assert_eq!(program.get_balance(&maker).unwrap(), maker_lamports + escrow_rent + vault_rent - bounty);
+        assert_eq!(program.get_balance(&cleaner.pubkey()).unwrap(), cleaner_lamports + bounty - 5000);
+        assert!(is_closed(&program, &vault));
+        assert!(is_closed(&program, &escrow));
+    }
+
+    fn take_ix(taker: &Pubkey, maker: &Pubkey, mint_a: &Pubkey, mint_b: &Pubkey, escrow: &Pubkey, vault: &Pubkey, expected_receive: Option<u64>, token_program: &Pubkey) -> Instruction {
+        take_with_feed_ix(taker, maker, mint_a, mint_b, escrow, vault, None, expected_receive, token_program)
+    }
+
+    fn take_with_feed_ix(taker: &Pubkey, maker: &Pubkey, mint_a: &Pubkey, mint_b: &Pubkey, escrow: &Pubkey, vault: &Pubkey, price_feed: Option<Pubkey>, expected_receive: Option<u64>, token_program: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Take {
+                taker: *taker,
+                maker: *maker,
+                mint_a: *mint_a,
+                mint_b: *mint_b,
+                taker_ata_a: ata(taker, mint_a, token_program),
+                taker_ata_b: ata(taker, mint_b, token_program),
+                maker_ata_b: ata(maker, mint_b, token_program),
+                escrow: *escrow,
+                vault: *vault,
+                order_book: Some(order_book_pda(mint_a, mint_b)),
+                price_feed,
+                config: config_pda(),
+                treasury: TREASURY,
+                treasury_ata_b: ata(&TREASURY, mint_b, token_program),
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: *token_program,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::Take { expected_receive }.data(),
+        }
+    }
+
+    fn update_offer_ix(maker: &Pubkey, mint_a: &Pubkey, mint_b: &Pubkey, escrow: &Pubkey, vault: &Pubkey, new_receive: u64, add_deposit: i64) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::UpdateOffer {
+                maker: *maker,
+                mint_a: *mint_a,
+                maker_ata_a: associated_token::get_associated_token_address(maker, mint_a),
+                escrow: *escrow,
+                vault: *vault,
+                order_book: Some(order_book_pda(mint_a, mint_b)),
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::UpdateOffer { new_receive, add_deposit }.data(),
+        }
+    }
+
+    #[test]
+    fn test_update_offer() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+
+        let (mint_a, mint_b, escrow, vault) = make_offer(&mut program, &payer, 9, 100, 50, 0, &TOKEN_PROGRAM_ID);
+        let (taker, _) = setup_taker(&mut program, &payer, &mint_b, &TOKEN_PROGRAM_ID);
+        let taker_ata_a = associated_token::get_associated_token_address(&taker.pubkey(), &mint_a);
+        let maker_ata_b = associated_token::get_associated_token_address(&maker, &mint_b);
+
+        // only the maker can reprice
+        let mut ix = update_offer_ix(&maker, &mint_a, &mint_b, &escrow, &vault, 1, 0);
+        ix.accounts[0] = solana_instruction::AccountMeta::new(taker.pubkey(), true);
+        assert!(send_ix(&mut program, ix, &[&taker]).is_err());
+
+        // reprice and top up in one go
+        let ix = update_offer_ix(&maker, &mint_a, &mint_b, &escrow, &vault, 80, 50);
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+        assert_eq!(token_balance(&program, &vault), 150);
+
+        // partial withdrawals are fine, emptying the vault is not
+        let ix = update_offer_ix(&maker, &mint_a, &mint_b, &escrow, &vault, 80, -150);
+        assert_escrow_error(send_ix(&mut program, ix, &[&payer]), crate::error::EscrowError::InvalidDepositChange);
+        let ix = update_offer_ix(&maker, &mint_a, &mint_b, &escrow, &vault, 80, -30);
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+
+        let escrow_data = crate::state::Escrow::try_deserialize(
+            &mut program.get_account(&escrow).unwrap().data.as_ref()
+        ).unwrap();
+        assert_eq!(escrow_data.receive, 80);
+        assert_eq!(escrow_data.remaining_deposit, 120);
+
+        // a taker quoting the old price is protected
+        let ix = take_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, Some(50), &TOKEN_PROGRAM_ID);
+        assert_escrow_error(send_ix(&mut program, ix, &[&taker]), crate::error::EscrowError::PriceChanged);
+
+        let ix = take_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, Some(80), &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&taker]).unwrap();
+        assert_eq!(token_balance(&program, &taker_ata_a), 120);
+        assert_eq!(token_balance(&program, &maker_ata_b), 80);
+    }
+
+    fn update_config_ix(admin: &Pubkey, fee_bps: Option<u16>, paused: Option<bool>) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::UpdateConfig {
+                admin: *admin,
+                config: config_pda(),
+            }.to_account_metas(None),
+            data: crate::instruction::UpdateConfig { fee_bps, treasury: None, paused }.data(),
+        }
+    }
+
+    fn refund_ix(maker: &Pubkey, mint_a: &Pubkey, mint_b: &Pubkey, escrow: &Pubkey, vault: &Pubkey, token_program: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::Refund {
+                maker: *maker,
+                mint_a: *mint_a,
+                maker_ata_a: ata(maker, mint_a, token_program),
+                escrow: *escrow,
+                vault: *vault,
+                order_book: Some(order_book_pda(mint_a, mint_b)),
+                token_program: *token_program,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::Refund {}.data(),
+        }
+    }
+
+    #[test]
+    fn test_take_fee() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+
+        // only the admin can change the fee
+        let stranger = Keypair::new();
+        program.airdrop(&stranger.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        let ix = update_config_ix(&stranger.pubkey(), Some(250), None);
+        assert!(send_ix(&mut program, ix, &[&stranger]).is_err());
+        let ix = update_config_ix(&maker, Some(10_001), None);
+        assert_escrow_error(send_ix(&mut program, ix, &[&payer]), crate::error::EscrowError::InvalidFee);
+
+        // 2.5%
+        let ix = update_config_ix(&maker, Some(250), None);
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+
+        let (mint_a, mint_b, escrow, vault) = make_offer(&mut program, &payer, 10, 100, 1000, 0, &TOKEN_PROGRAM_ID);
+        let (taker, taker_ata_b) = setup_taker(&mut program, &payer, &mint_b, &TOKEN_PROGRAM_ID);
+        let ix = take_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, None, &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&taker]).unwrap();
+
+        let treasury_ata_b = associated_token::get_associated_token_address(&TREASURY, &mint_b);
+        let maker_ata_b = associated_token::get_associated_token_address(&maker, &mint_b);
+        assert_eq!(token_balance(&program, &taker_ata_b), 1000000000 - 1000);
+        assert_eq!(token_balance(&program, &treasury_ata_b), 25);
+        assert_eq!(token_balance(&program, &maker_ata_b), 975);
+    }
+
+    #[test]
+    fn test_pause() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+
+        let (mint_a, mint_b, escrow, vault) = make_offer(&mut program, &payer, 11, 100, 50, 0, &TOKEN_PROGRAM_ID);
+        let (taker, _) = setup_taker(&mut program, &payer, &mint_b, &TOKEN_PROGRAM_ID);
+
+        let ix = update_config_ix(&maker, None, Some(true));
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+
+        // no new offers and no takes while paused
+        let ix = take_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, None, &TOKEN_PROGRAM_ID);
+        assert_escrow_error(send_ix(&mut program, ix, &[&taker]), crate::error::EscrowError::Paused);
+        let ix = take_partial_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, 10);
+        assert_escrow_error(send_ix(&mut program, ix, &[&taker]), crate::error::EscrowError::Paused);
+        let ix = make_ix(&maker, &mint_a, &mint_b, 12, 100, 50, 0, &TOKEN_PROGRAM_ID);
+        assert_escrow_error(send_ix(&mut program, ix, &[&payer]), crate::error::EscrowError::Paused);
+
+        // makers can still get their deposit back
+        let maker_ata_a = associated_token::get_associated_token_address(&maker, &mint_a);
+        let before = token_balance(&program, &maker_ata_a);
+        let ix = refund_ix(&maker, &mint_a, &mint_b, &escrow, &vault, &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+        assert_eq!(token_balance(&program, &maker_ata_a), before + 100);
+    }
+
+    // Helper: Write a mock oracle price account directly, owned by `owner`
+    fn set_price_feed_owned_by(program: &mut LiteSVM, feed: &Pubkey, price: u64, expo: i32, publish_time: i64, owner: Pubkey) {
+        let mut data = vec![];
+        mock_oracle::PriceFeed { authority: Pubkey::default(), price, expo, publish_time }
+            .try_serialize(&mut data)
+            .unwrap();
+        program.set_account(*feed, Account {
+            lamports: program.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        }).unwrap();
+    }
+
+    fn set_price_feed(program: &mut LiteSVM, feed: &Pubkey, price: u64, expo: i32, publish_time: i64) {
+        set_price_feed_owned_by(program, feed, price, expo, publish_time, mock_oracle::ID);
+    }
+
+    #[test]
+    fn test_take_oracle_priced() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+        let now = 1_700_000_000;
+        warp_to(&mut program, now);
+
+        let mint_a = CreateMint::new(&mut program, &payer).decimals(6).authority(&maker).send().unwrap();
+        let mint_b = CreateMint::new(&mut program, &payer).decimals(6).authority(&maker).send().unwrap();
+        let maker_ata_a = CreateAssociatedTokenAccount::new(&mut program, &payer, &mint_a)
+            .owner(&maker).send().unwrap();
+        MintTo::new(&mut program, &payer, &mint_a, &maker_ata_a, 1000000000).send().unwrap();
+
+        // the escrow reads the mock oracle's accounts without linking it
+        assert_eq!(crate::state::ORACLE_PROGRAM_ID, mock_oracle::ID);
+
+        // 0.2 B per A, plus 1%
+        let feed = Pubkey::new_unique();
+        set_price_feed(&mut program, &feed, 2, -1, now);
+
+        let seed = 13;
+        let escrow = escrow_pda(&maker, seed);
+        let vault = associated_token::get_associated_token_address(&escrow, &mint_a);
+        let mut ix = make_ix(&maker, &mint_a, &mint_b, seed, 1_000_000, 0, 0, &TOKEN_PROGRAM_ID);
+        ix.data = crate::instruction::MakeOracle { seed, deposit: 1_000_000, price_feed: feed, spread_bps: 100, expiry: 0 }.data();
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+
+        let (taker, taker_ata_b) = setup_taker(&mut program, &payer, &mint_b, &TOKEN_PROGRAM_ID);
+        let taker_ata_a = associated_token::get_associated_token_address(&taker.pubkey(), &mint_a);
+        let maker_ata_b = associated_token::get_associated_token_address(&maker, &mint_b);
+
+        // the feed has to be passed, and has to be the one the offer was made with
+        let ix = take_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, None, &TOKEN_PROGRAM_ID);
+        assert_escrow_error(send_ix(&mut program, ix, &[&taker]), crate::error::EscrowError::MissingPriceFeed);
+        let other_feed = Pubkey::new_unique();
+        set_price_feed(&mut program, &other_feed, 1, -1, now);
+        let ix = take_with_feed_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, Some(other_feed), None, &TOKEN_PROGRAM_ID);
+        assert_escrow_error(send_ix(&mut program, ix, &[&taker]), crate::error::EscrowError::MissingPriceFeed);
+
+        // the maker picks the feed, so the taker has to bound what they pay
+        let ix = take_with_feed_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, Some(feed), None, &TOKEN_PROGRAM_ID);
+        assert_escrow_error(send_ix(&mut program, ix, &[&taker]), crate::error::EscrowError::MissingPriceBound);
+
+        // slippage bound below the quote
+        let ix = take_with_feed_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, Some(feed), Some(201_999), &TOKEN_PROGRAM_ID);
+        assert_escrow_error(send_ix(&mut program, ix, &[&taker]), crate::error::EscrowError::PriceChanged);
+
+        // stale price
+        warp_to(&mut program, now + crate::state::MAX_PRICE_AGE + 1);
+        let ix = take_with_feed_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, Some(feed), None, &TOKEN_PROGRAM_ID);
+        assert_escrow_error(send_ix(&mut program, ix, &[&taker]), crate::error::EscrowError::StalePrice);
+
+        // the feed has to be published by the oracle program
+        set_price_feed_owned_by(&mut program, &feed, 1, -1, now + crate::state::MAX_PRICE_AGE, PROGRAM_ID);
+        let ix = take_with_feed_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, Some(feed), Some(202_000), &TOKEN_PROGRAM_ID);
+        let err = send_ix(&mut program, ix, &[&taker]).expect_err("Feed owned by another program");
+        assert_eq!(
+            format!("{:?}", err.err),
+            format!("InstructionError(0, Custom({}))", anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram as u32)
+        );
+
+        // fresh price
+        set_price_feed(&mut program, &feed, 2, -1, now + crate::state::MAX_PRICE_AGE);
+        let ix = take_with_feed_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow, &vault, Some(feed), Some(202_000), &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&taker]).unwrap();
+
+        assert_eq!(token_balance(&program, &taker_ata_a), 1_000_000);
+        assert_eq!(token_balance(&program, &maker_ata_b), 202_000);
+        assert_eq!(token_balance(&program, &taker_ata_b), 1000000000 - 202_000);
+    }
+
+    // Helper: The escrows listed for a pair, best price first
+    fn listed_orders(program: &LiteSVM, mint_a: &Pubkey, mint_b: &Pubkey) -> Vec<(Pubkey, u64, u64)> {
+        let account = program.get_account(&order_book_pda(mint_a, mint_b)).unwrap();
+        let order_book: crate::state::OrderBook = anchor_lang::__private::bytemuck::pod_read_unaligned(
+            &account.data[8..crate::state::OrderBook::SPACE]
+        );
+        order_book.orders().iter().map(|entry| (entry.escrow, entry.deposit, entry.receive)).collect()
+    }
+
+    fn take_best_ix(taker: &Pubkey, maker: &Pubkey, mint_a: &Pubkey, mint_b: &Pubkey, escrow: &Pubkey, vault: &Pubkey, max_receive: u64) -> Instruction {
+        let mut ix = take_ix(taker, maker, mint_a, mint_b, escrow, vault, None, &TOKEN_PROGRAM_ID);
+        ix.data = crate::instruction::TakeBest { max_receive }.data();
+        ix
+    }
+
+    #[test]
+    fn test_order_book() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+
+        // 0.5, 0.3 and 0.4 B per A
+        let (mint_a, mint_b, escrow_1, vault_1) = make_offer(&mut program, &payer, 21, 100, 50, 0, &TOKEN_PROGRAM_ID);
+        let ix = make_ix(&maker, &mint_a, &mint_b, 22, 100, 30, 0, &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+        let ix = make_ix(&maker, &mint_a, &mint_b, 23, 100, 40, 0, &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+        let (escrow_2, escrow_3) = (escrow_pda(&maker, 22), escrow_pda(&maker, 23));
+        let vault_2 = associated_token::get_associated_token_address(&escrow_2, &mint_a);
+        let vault_3 = associated_token::get_associated_token_address(&escrow_3, &mint_a);
+
+        assert_eq!(
+            listed_orders(&program, &mint_a, &mint_b),
+            vec![(escrow_2, 100, 30), (escrow_3, 100, 40), (escrow_1, 100, 50)]
+        );
+
+        // only the head of the book, and only within the taker's limit
+        let (taker, taker_ata_b) = setup_taker(&mut program, &payer, &mint_b, &TOKEN_PROGRAM_ID);
+        let ix = take_best_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow_1, &vault_1, 50);
+        assert_escrow_error(send_ix(&mut program, ix, &[&taker]), crate::error::EscrowError::NotBestOffer);
+        let ix = take_best_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow_2, &vault_2, 29);
+        assert_escrow_error(send_ix(&mut program, ix, &[&taker]), crate::error::EscrowError::PriceChanged);
+
+        let ix = take_best_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow_2, &vault_2, 30);
+        send_ix(&mut program, ix, &[&taker]).unwrap();
+        assert_eq!(token_balance(&program, &taker_ata_b), 1000000000 - 30);
+        assert_eq!(
+            listed_orders(&program, &mint_a, &mint_b),
+            vec![(escrow_3, 100, 40), (escrow_1, 100, 50)]
+        );
+
+        // partial fills and repricing keep the entries current and sorted
+        let ix = take_partial_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow_3, &vault_3, 20);
+        send_ix(&mut program, ix, &[&taker]).unwrap();
+        let ix = update_offer_ix(&maker, &mint_a, &mint_b, &escrow_3, &vault_3, 40, 0);
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+        assert_eq!(
+            listed_orders(&program, &mint_a, &mint_b),
+            vec![(escrow_1, 100, 50), (escrow_3, 50, 40)]
+        );
+
+        // refunds and plain takes unlist the offer
+        let ix = refund_ix(&maker, &mint_a, &mint_b, &escrow_1, &vault_1, &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+        assert_eq!(listed_orders(&program, &mint_a, &mint_b), vec![(escrow_3, 50, 40)]);
+
+        let ix = take_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow_3, &vault_3, Some(40), &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&taker]).unwrap();
+        assert!(listed_orders(&program, &mint_a, &mint_b).is_empty());
+    }
+
+    #[test]
+    fn test_order_book_full() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+
+        let (mint_a, mint_b, _, _) = make_offer(&mut program, &payer, 100, 10, 50, 0, &TOKEN_PROGRAM_ID);
+        for seed in 101..100 + crate::state::MAX_ORDERS as u64 {
+            let ix = make_ix(&maker, &mint_a, &mint_b, seed, 10, 50, 0, &TOKEN_PROGRAM_ID);
+            send_ix(&mut program, ix, &[&payer]).unwrap();
+        }
+        assert_eq!(listed_orders(&program, &mint_a, &mint_b).len(), crate::state::MAX_ORDERS);
+
+        // a full book does not stop a worse offer from being made, it only leaves it unlisted
+        let worse = escrow_pda(&maker, 200);
+        let ix = make_ix(&maker, &mint_a, &mint_b, 200, 10, 60, 0, &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+        let listed = listed_orders(&program, &mint_a, &mint_b);
+        assert_eq!(listed.len(), crate::state::MAX_ORDERS);
+        assert!(listed.iter().all(|entry| entry.0 != worse));
+
+        // a better one takes the place of the worst entry, the last listed of the tied ones
+        let evicted = escrow_pda(&maker, 99 + crate::state::MAX_ORDERS as u64);
+        let ix = make_ix(&maker, &mint_a, &mint_b, 201, 10, 1, 0, &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+        let listed = listed_orders(&program, &mint_a, &mint_b);
+        assert_eq!(listed.len(), crate::state::MAX_ORDERS);
+        assert_eq!(listed[0].0, escrow_pda(&maker, 201));
+        assert!(listed.iter().all(|entry| entry.0 != evicted));
+
+        // offers left out of the book still refund
+        for escrow in [worse, evicted] {
+            let vault = associated_token::get_associated_token_address(&escrow, &mint_a);
+            let ix = refund_ix(&maker, &mint_a, &mint_b, &escrow, &vault, &TOKEN_PROGRAM_ID);
+            send_ix(&mut program, ix, &[&payer]).unwrap();
+            assert!(is_closed(&program, &escrow));
+        }
+        assert_eq!(listed_orders(&program, &mint_a, &mint_b).len(), crate::state::MAX_ORDERS);
+    }
+
+    // Helper: Leave the order book out, as clients of offers made before order books do
+    fn without_order_book(mut ix: Instruction, mint_a: &Pubkey, mint_b: &Pubkey) -> Instruction {
+        let order_book = order_book_pda(mint_a, mint_b);
+        for meta in ix.accounts.iter_mut().filter(|meta| meta.pubkey == order_book) {
+            *meta = solana_instruction::AccountMeta::new_readonly(PROGRAM_ID, false);
+        }
+        ix
+    }
+
+    #[test]
+    fn test_init_order_book() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+
+        // offers made before order books existed
+        let (mint_a, mint_b, escrow, vault) = make_offer(&mut program, &payer, 31, 100, 50, 0, &TOKEN_PROGRAM_ID);
+        let ix = make_ix(&maker, &mint_a, &mint_b, 32, 100, 50, 0, &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+        let escrow_2 = escrow_pda(&maker, 32);
+        let vault_2 = associated_token::get_associated_token_address(&escrow_2, &mint_a);
+        program.set_account(order_book_pda(&mint_a, &mint_b), Account::default()).unwrap();
+
+        // settle without a book
+        let ix = without_order_book(refund_ix(&maker, &mint_a, &mint_b, &escrow, &vault, &TOKEN_PROGRAM_ID), &mint_a, &mint_b);
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+        let (taker, _) = setup_taker(&mut program, &payer, &mint_b, &TOKEN_PROGRAM_ID);
+        let ix = take_partial_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow_2, &vault_2, 10);
+        send_ix(&mut program, without_order_book(ix, &mint_a, &mint_b), &[&taker]).unwrap();
+
+        // anyone can create the missing book
+        let stranger = Keypair::new();
+        program.airdrop(&stranger.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::InitOrderBook {
+                payer: stranger.pubkey(),
+                mint_a,
+                mint_b,
+                order_book: order_book_pda(&mint_a, &mint_b),
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::InitOrderBook {}.data(),
+        };
+        send_ix(&mut program, ix, &[&stranger]).unwrap();
+        assert!(listed_orders(&program, &mint_a, &mint_b).is_empty());
+
+        // the unlisted offer can be repriced and taken with it
+        let ix = update_offer_ix(&maker, &mint_a, &mint_b, &escrow_2, &vault_2, 40, 0);
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+        assert!(listed_orders(&program, &mint_a, &mint_b).is_empty());
+        let ix = take_ix(&taker.pubkey(), &maker, &mint_a, &mint_b, &escrow_2, &vault_2, Some(40), &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&taker]).unwrap();
+    }
+
+    #[test]
+    fn test_refund_legacy() {
+        let (mut program, payer) = setup();
+        let maker = payer.pubkey();
+
+        // an offer made before escrows were versioned, in the old layout at the old address
+        let mint_a = CreateMint::new(&mut program, &payer).decimals(6).authority(&maker).send().unwrap();
+        let mint_b = CreateMint::new(&mut program, &payer).decimals(6).authority(&maker).send().unwrap();
+        let maker_ata_a = CreateAssociatedTokenAccount::new(&mut program, &payer, &mint_a)
+            .owner(&maker).send().unwrap();
+        let (escrow, bump) = Pubkey::find_program_address(
+            &[b"escrow", maker.as_ref(), &41u64.to_le_bytes()],
+            &PROGRAM_ID
+        );
+        let mut data = crate::state::Escrow::DISCRIMINATOR.to_vec();
+        anchor_lang::AnchorSerialize::serialize(&crate::state::LegacyEscrow {
+            seed: 41,
+            maker,
+            mint_a,
+            mint_b,
+            receive: 50,
+            bump,
+        }, &mut data).unwrap();
+        program.set_account(escrow, Account {
+            lamports: program.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        }).unwrap();
+        let vault = CreateAssociatedTokenAccount::new(&mut program, &payer, &mint_a)
+            .owner(&escrow).send().unwrap();
+        MintTo::new(&mut program, &payer, &mint_a, &vault, 100).send().unwrap();
+
+        // the current instructions do not read it
+        let ix = refund_ix(&maker, &mint_a, &mint_b, &escrow, &vault, &TOKEN_PROGRAM_ID);
+        assert!(send_ix(&mut program, ix, &[&payer]).is_err());
+
+        let refund_legacy_ix = |maker: Pubkey| Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::RefundLegacy {
+                maker,
+                mint_a,
+                maker_ata_a: associated_token::get_associated_token_address(&maker, &mint_a),
+                escrow,
+                vault,
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::RefundLegacy {}.data(),
+        };
+
+        // only by its maker
+        let stranger = Keypair::new();
+        program.airdrop(&stranger.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        CreateAssociatedTokenAccount::new(&mut program, &stranger, &mint_a)
+            .owner(&stranger.pubkey()).send().unwrap();
+        assert!(send_ix(&mut program, refund_legacy_ix(stranger.pubkey()), &[&stranger]).is_err());
+
+        send_ix(&mut program, refund_legacy_ix(maker), &[&payer]).unwrap();
+        assert_eq!(token_balance(&program, &maker_ata_a), 100);
+        assert!(is_closed(&program, &vault));
+        assert!(is_closed(&program, &escrow));
+
+        // current escrows are not legacy ones
+        let (mint_a, _, escrow, vault) = make_offer(&mut program, &payer, 41, 100, 50, 0, &TOKEN_PROGRAM_ID);
+        let mut ix = refund_legacy_ix(maker);
+        ix.accounts[1].pubkey = mint_a;
+        ix.accounts[2].pubkey = associated_token::get_associated_token_address(&maker, &mint_a);
+        ix.accounts[3].pubkey = escrow;
+        ix.accounts[4].pubkey = vault;
+        assert!(send_ix(&mut program, ix, &[&payer]).is_err());
+    }
+
+    fn match_offers_ix(caller: &Pubkey, maker_x: &Pubkey, maker_y: &Pubkey, mint_a: &Pubkey, mint_b: &Pubkey, seed_x: u64, seed_y: u64, matching_fee: u64) -> Instruction {
+        let ata = associated_token::get_associated_token_address;
+        let (escrow_x, escrow_y) = (escrow_pda(maker_x, seed_x), escrow_pda(maker_y, seed_y));
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::MatchOffers {
+                caller: *caller,
+                maker_x: *maker_x,
+                maker_y: *maker_y,
+                mint_a: *mint_a,
+                mint_b: *mint_b,
+                escrow_x,
+                escrow_y,
+                vault_x: ata(&escrow_x, mint_a),
+                vault_y: ata(&escrow_y, mint_b),
+                maker_x_ata_a: ata(maker_x, mint_a),
+                maker_x_ata_b: ata(maker_x, mint_b),
+                maker_y_ata_a: ata(maker_y, mint_a),
+                maker_y_ata_b: ata(maker_y, mint_b),
+                order_book_x: Some(order_book_pda(mint_a, mint_b)),
+                order_book_y: Some(order_book_pda(mint_b, mint_a)),
+                config: config_pda(),
+                treasury: TREASURY,
+                treasury_ata_b: ata(&TREASURY, mint_b),
+                caller_ata_b: (matching_fee > 0).then(|| ata(caller, mint_b)),
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::MatchOffers { matching_fee }.data(),
+        }
+    }
+
+    #[test]
+    fn test_match_offers() {
+        let (mut program, payer) = setup();
+        let maker_x = payer.pubkey();
+        let matcher = Keypair::new();
+        program.airdrop(&matcher.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        // x sells 100 A for 50 B, y sells 80 B for 100 A, 30 B of surplus
+        let (mint_a, mint_b, escrow_x, vault_x) = make_offer(&mut program, &payer, 31, 100, 50, 0, &TOKEN_PROGRAM_ID);
+        let (y, maker_y_ata_b) = setup_taker(&mut program, &payer, &mint_b, &TOKEN_PROGRAM_ID);
+        let maker_y = y.pubkey();
+        let ix = make_ix(&maker_y, &mint_b, &mint_a, 32, 80, 100, 0, &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&y]).unwrap();
+        let escrow_y = escrow_pda(&maker_y, 32);
+        let vault_y = associated_token::get_associated_token_address(&escrow_y, &mint_b);
+        let matcher_ata_b = CreateAssociatedTokenAccount::new(&mut program, &matcher, &mint_b)
+            .owner(&matcher.pubkey()).send().unwrap();
+
+        let ix = match_offers_ix(&matcher.pubkey(), &maker_x, &maker_y, &mint_a, &mint_b, 31, 32, 31);
+        assert_escrow_error(send_ix(&mut program, ix, &[&matcher]), crate::error::EscrowError::MatchingFeeTooHigh);
+
+        let ix = match_offers_ix(&matcher.pubkey(), &maker_x, &maker_y, &mint_a, &mint_b, 31, 32, 10);
+        send_ix(&mut program, ix, &[&matcher]).unwrap();
+
+        // x gets its price, y gets its A and keeps the surplus the matcher left
+        let ata = associated_token::get_associated_token_address;
+        assert_eq!(token_balance(&program, &ata(&maker_x, &mint_b)), 50);
+        assert_eq!(token_balance(&program, &ata(&maker_y, &mint_a)), 100);
+        assert_eq!(token_balance(&program, &maker_y_ata_b), 1000000000 - 80 + 20);
+        assert_eq!(token_balance(&program, &matcher_ata_b), 10);
+        for closed in [escrow_x, vault_x, escrow_y, vault_y] {
+            assert!(is_closed(&program, &closed));
+        }
+        assert!(listed_orders(&program, &mint_a, &mint_b).is_empty());
+        assert!(listed_orders(&program, &mint_b, &mint_a).is_empty());
+
+        // y only wants 50 A now: x is filled halfway and stays listed
+        let ix = make_ix(&maker_x, &mint_a, &mint_b, 33, 100, 50, 0, &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&payer]).unwrap();
+        let ix = make_ix(&maker_y, &mint_b, &mint_a, 34, 40, 50, 0, &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&y]).unwrap();
+        let ix = match_offers_ix(&matcher.pubkey(), &maker_x, &maker_y, &mint_a, &mint_b, 33, 34, 0);
+        send_ix(&mut program, ix, &[&matcher]).unwrap();
+
+        assert_eq!(token_balance(&program, &ata(&maker_x, &mint_b)), 50 + 25);
+        assert_eq!(token_balance(&program, &ata(&maker_y, &mint_a)), 100 + 50);
+        assert_eq!(token_balance(&program, &maker_y_ata_b), 1000000000 - 80 + 20 - 25);
+        assert_eq!(listed_orders(&program, &mint_a, &mint_b), vec![(escrow_pda(&maker_x, 33), 50, 25)]);
+        assert!(listed_orders(&program, &mint_b, &mint_a).is_empty());
+
+        // 0.5 B per A asked against 0.4 B per A offered
+        let ix = make_ix(&maker_y, &mint_b, &mint_a, 35, 20, 50, 0, &TOKEN_PROGRAM_ID);
+        send_ix(&mut program, ix, &[&y]).unwrap();
+        let ix = match_offers_ix(&matcher.pubkey(), &maker_x, &maker_y, &mint_a, &mint_b, 33, 35, 0);
+        assert_escrow_error(send_ix(&mut program, ix, &[&matcher]), crate::error::EscrowError::PricesDoNotCross);
+    }
+
+    // Take/refund matrix
+    //
+    // Every case starts from the same offer, 100 A for 40 B, made on the given token
+    // program, runs its actions in order and checks where the tokens ended up.
+
+    const DEPOSIT: u64 = 100;
+    const RECEIVE: u64 = 40;
+    // what make_offer and setup_taker fund each account with
+    const INITIAL: u64 = 1_000_000_000;
+
+    struct Market {
+        program: LiteSVM,
+        maker: Keypair,
+        taker: Keypair,
+        token_program: Pubkey,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        // a mint nobody asked for, the taker holds some
+        mint_c: Pubkey,
+        escrow: Pubkey,
+        vault: Pubkey,
+    }
+
+    impl Market {
+        fn open(token_program: Pubkey) -> Self {
+            let (mut program, maker) = setup();
+            let (mint_a, mint_b, escrow, vault) = make_offer(&mut program, &maker, 42, DEPOSIT, RECEIVE, 0, &token_program);
+            let (taker, _) = setup_taker(&mut program, &maker, &mint_b, &token_program);
+            let mint_c = create_mint(&mut program, &maker, &token_program);
+            create_funded_ata(&mut program, &maker, &taker.pubkey(), &mint_c, &token_program);
+
+            Self { program, maker, taker, token_program, mint_a, mint_b, mint_c, escrow, vault }
+        }
+
+        fn send(&mut self, ix: Instruction, as_maker: bool) -> litesvm::types::TransactionResult {
+            // identical retries would otherwise be rejected as already processed
+            self.program.expire_blockhash();
+            let signer = if as_maker { &self.maker } else { &self.taker };
+            send_ix(&mut self.program, ix, &[signer])
+        }
+
+        fn take_ix(&self, maker: &Pubkey, mint_b: &Pubkey) -> Instruction {
+            take_ix(&self.taker.pubkey(), maker, &self.mint_a, mint_b, &self.escrow, &self.vault, None, &self.token_program)
+        }
+
+        fn run(&mut self, action: Action) -> litesvm::types::TransactionResult {
+            match action {
+                Action::Take => self.send(self.take_ix(&self.maker.pubkey(), &self.mint_b), false),
+                Action::TakeWrongMintB => self.send(self.take_ix(&self.maker.pubkey(), &self.mint_c), false),
+                Action::TakeWrongMaker => self.send(self.take_ix(&self.taker.pubkey(), &self.mint_b), false),
+                Action::Refund => {
+                    let ix = refund_ix(&self.maker.pubkey(), &self.mint_a, &self.mint_b, &self.escrow, &self.vault, &self.token_program);
+                    self.send(ix, true)
+                }
+            }
+        }
+
+        fn assert_final(&self, expected: Final, case: &str) {
+            let maker = self.maker.pubkey();
+            let taker = self.taker.pubkey();
+            let maker_ata_a = ata(&maker, &self.mint_a, &self.token_program);
+            let taker_ata_a = ata(&taker, &self.mint_a, &self.token_program);
+            let maker_ata_b = ata(&maker, &self.mint_b, &self.token_program);
+            let taker_ata_b = ata(&taker, &self.mint_b, &self.token_program);
+
+            match expected {
+                Final::Open => {
+                    assert_eq!(token_balance(&self.program, &self.vault), DEPOSIT, "{}", case);
+                    assert_eq!(token_balance(&self.program, &maker_ata_a), INITIAL - DEPOSIT, "{}", case);
+                    assert_eq!(token_balance(&self.program, &taker_ata_b), INITIAL, "{}", case);
+                    assert!(!is_closed(&self.program, &self.escrow), "{}", case);
+                }
+                Final::Taken => {
+                    assert_eq!(token_balance(&self.program, &maker_ata_a), INITIAL - DEPOSIT, "{}", case);
+                    assert_eq!(token_balance(&self.program, &taker_ata_a), DEPOSIT, "{}", case);
+                    assert_eq!(token_balance(&self.program, &maker_ata_b), RECEIVE, "{}", case);
+                    assert_eq!(token_balance(&self.program, &taker_ata_b), INITIAL - RECEIVE, "{}", case);
+                    assert!(is_closed(&self.program, &self.vault), "{}", case);
+                    assert!(is_closed(&self.program, &self.escrow), "{}", case);
+                }
+                Final::Refunded => {
+                    assert_eq!(token_balance(&self.program, &maker_ata_a), INITIAL, "{}", case);
+                    assert_eq!(token_balance(&self.program, &taker_ata_b), INITIAL, "{}", case);
+                    assert!(is_closed(&self.program, &taker_ata_a), "{}", case);
+                    assert!(is_closed(&self.program, &self.vault), "{}", case);
+                    assert!(is_closed(&self.program, &self.escrow), "{}", case);
+                }
+            }
+            assert_eq!(token_balance(&self.program, &ata(&taker, &self.mint_c, &self.token_program)), INITIAL, "{}", case);
+        }
+    }
+
+    #[derive(Clone, Copy, Debug)]
+    enum Action {
+        Take,
+        TakeWrongMintB,
+        TakeWrongMaker,
+        Refund,
+    }
+
+    #[derive(Clone, Copy, Debug)]
+    enum Final {
+        Open,
+        Taken,
+        Refunded,
+    }
+
+    #[test]
+    fn test_take_refund_matrix() {
+        use Action::*;
+
+        let legacy = TOKEN_PROGRAM_ID;
+        let token_2022 = spl_token_2022::ID;
+
+        // (case, token program, actions and whether each should succeed, final state)
+        let cases: &[(&str, Pubkey, &[(Action, bool)], Final)] = &[
+            ("make -> take", legacy, &[(Take, true)], Final::Taken),
+            ("make -> take (token-2022)", token_2022, &[(Take, true)], Final::Taken),
+            ("make -> refund", legacy, &[(Refund, true)], Final::Refunded),
+            ("make -> refund (token-2022)", token_2022, &[(Refund, true)], Final::Refunded),
+            ("double take", legacy, &[(Take, true), (Take, false)], Final::Taken),
+            ("double take (token-2022)", token_2022, &[(Take, true), (Take, false)], Final::Taken),
+            ("take after refund", legacy, &[(Refund, true), (Take, false)], Final::Refunded),
+            ("refund after take", legacy, &[(Take, true), (Refund, false)], Final::Taken),
+            ("wrong mint_b", legacy, &[(TakeWrongMintB, false)], Final::Open),
+            ("wrong mint_b (token-2022)", token_2022, &[(TakeWrongMintB, false)], Final::Open),
+            ("wrong maker", legacy, &[(TakeWrongMaker, false)], Final::Open),
+            ("wrong maker, then take", legacy, &[(TakeWrongMaker, false), (Take, true)], Final::Taken),
+        ];
+
+        for (case, token_program, actions, expected) in cases {
+            let mut market = Market::open(*token_program);
+            for (action, should_succeed) in actions.iter() {
+                let result = market.run(*action);
+                assert_eq!(
+                    result.is_ok(),
+                    *should_succeed,
+                    "{}: {:?} {:#?}",
+                    case,
+                    action,
+                    result.map(|tx| tx.logs).unwrap_or_else(|e| e.meta.logs)
+                );
+            }
+            market.assert_final(*expected, case);
+        }
+    }
+
+}
@@ -0,0 +1,59 @@
+#![allow(unexpected_cfgs)]
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+
+declare_id!("zL6AYha7is9BdNNBuhnoVDTQzfYEYSdSgFWZtSbvkHY");
+
+#[program]
+pub mod mock_oracle {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>, price: u64, expo: i32) -> Result<()> {
+        ctx.accounts.feed.set_inner(PriceFeed {
+            authority: ctx.accounts.authority.key(),
+            price,
+            expo,
+            publish_time: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn set_price(ctx: Context<SetPrice>, price: u64, expo: i32) -> Result<()> {
+        let feed = &mut ctx.accounts.feed;
+        feed.price = price;
+        feed.expo = expo;
+        feed.publish_time = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+}
+
+// price * 10^expo units of the quote mint per base unit of the priced mint
+#[account]
+#[derive(InitSpace, Debug)]
+pub struct PriceFeed {
+    pub authority: Pubkey,
+    pub price: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PriceFeed::INIT_SPACE,
+    )]
+    pub feed: Account<'info, PriceFeed>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPrice<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = authority)]
+    pub feed: Account<'info, PriceFeed>,
+}
@@ -0,0 +1,491 @@
+// @dev host-side helpers for dashboards and scripts, only built with the `client` feature.
+// Decodes program accounts into typed views and builds every instruction with the
+// account order the program expects.
+
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+use crate::{
+    instructions::{FundraiserInstructions, InitializeInstruction},
+    merkle,
+    state::{AllowlistEntry, Contributor, Fundraiser, ALLOWLIST_MERKLE, ALLOWLIST_PDA},
+    FundraiserError,
+};
+
+pub fn program_id() -> Pubkey {
+    Pubkey::new_from_array(crate::ID)
+}
+
+fn system_program_id() -> Pubkey {
+    Pubkey::new_from_array(pinocchio_system::ID)
+}
+
+fn associated_token_program_id() -> Pubkey {
+    Pubkey::new_from_array(pinocchio_associated_token_account::ID)
+}
+
+fn rent_sysvar_id() -> Pubkey {
+    Pubkey::new_from_array(pinocchio::sysvars::rent::RENT_ID)
+}
+
+pub fn token_program_id() -> Pubkey {
+    Pubkey::new_from_array(pinocchio_token::ID)
+}
+
+pub fn token_2022_program_id() -> Pubkey {
+    Pubkey::new_from_array(crate::token_interface::TOKEN_2022_PROGRAM_ID)
+}
+
+// PDAs
+
+pub fn find_fundraiser_address(maker: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"fundraiser", maker.as_ref()], &program_id())
+}
+
+pub fn find_contributor_address(contributor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"contributor", contributor.as_ref()], &program_id())
+}
+
+// @dev temporary wSOL account native refunds are paid through
+pub fn find_refund_account_address(contributor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"refund", contributor.as_ref()], &program_id())
+}
+
+pub fn find_receipt_mint_address(fundraiser: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"receipt", fundraiser.as_ref()], &program_id())
+}
+
+pub fn find_allowlist_entry_address(fundraiser: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"allowlist", fundraiser.as_ref(), wallet.as_ref()],
+        &program_id(),
+    )
+}
+
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &associated_token_program_id(),
+    )
+    .0
+}
+
+// Account views
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AllowlistMode {
+    None,
+    Pda,
+    Merkle { root: [u8; 32] },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FundraiserStatus {
+    // accepting contributions
+    Active,
+    // goal met, raised funds belong to the maker
+    Funded,
+    // deadline passed without meeting the goal, contributors can refund
+    Refundable,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FundraiserView {
+    pub maker: Pubkey,
+    pub mint: Pubkey,
+    pub receipt_mint: Pubkey,
+    pub amount_to_raise: u64,
+    pub current_amount: u64,
+    pub time_started: i64,
+    pub duration: u64,
+    pub bump: u8,
+    pub hard_cap: Option<u64>,
+    pub goal_reached_at: Option<i64>,
+    pub vesting_duration: u64,
+    pub claimed_amount: u64,
+    pub allowlist: AllowlistMode,
+    raw: Fundraiser,
+}
+
+impl FundraiserView {
+    pub fn decode(data: &[u8]) -> Result<Self, FundraiserError> {
+        let state = bytemuck::try_from_bytes::<Fundraiser>(data)
+            .map_err(|_| FundraiserError::InvalidFundraiser)?;
+
+        let allowlist = match state.allowlist_mode[0] {
+            ALLOWLIST_PDA => AllowlistMode::Pda,
+            ALLOWLIST_MERKLE => AllowlistMode::Merkle {
+                root: state.merkle_root,
+            },
+            _ => AllowlistMode::None,
+        };
+        let nonzero = |value: u64| (value != 0).then_some(value);
+
+        Ok(Self {
+            maker: Pubkey::new_from_array(state.maker),
+            mint: Pubkey::new_from_array(state.mint),
+            receipt_mint: Pubkey::new_from_array(state.receipt_mint),
+            amount_to_raise: u64::from_le_bytes(state.amount_to_raise),
+            current_amount: u64::from_le_bytes(state.current_amount),
+            time_started: i64::from_le_bytes(state.time_started),
+            duration: u64::from_le_bytes(state.duration),
+            bump: state.bump[0],
+            hard_cap: nonzero(u64::from_le_bytes(state.hard_cap)),
+            goal_reached_at: nonzero(u64::from_le_bytes(state.goal_reached_at)).map(|t| t as i64),
+            vesting_duration: u64::from_le_bytes(state.vesting_duration),
+            claimed_amount: u64::from_le_bytes(state.claimed_amount),
+            allowlist,
+            raw: *state,
+        })
+    }
+
+    pub fn address(&self) -> Pubkey {
+        find_fundraiser_address(&self.maker).0
+    }
+
+    pub fn vault(&self, token_program: &Pubkey) -> Pubkey {
+        associated_token_address(&self.address(), &self.mint, token_program)
+    }
+
+    // @dev unix timestamp after which refunds open if the goal was not met
+    pub fn deadline(&self) -> i64 {
+        self.time_started.saturating_add(self.duration as i64)
+    }
+
+    pub fn time_remaining(&self, now: i64) -> u64 {
+        self.deadline().saturating_sub(now).max(0) as u64
+    }
+
+    pub fn goal_reached(&self) -> bool {
+        self.current_amount >= self.amount_to_raise
+    }
+
+    pub fn percent_funded(&self) -> f64 {
+        if self.amount_to_raise == 0 {
+            return 100.0;
+        }
+        self.current_amount as f64 * 100.0 / self.amount_to_raise as f64
+    }
+
+    // @dev how much more the campaign can take, `None` when uncapped
+    pub fn remaining_room(&self) -> Option<u64> {
+        self.hard_cap.map(|_| self.raw.remaining_room())
+    }
+
+    pub fn status(&self, now: i64) -> FundraiserStatus {
+        if self.goal_reached() {
+            FundraiserStatus::Funded
+        } else if now >= self.deadline() {
+            FundraiserStatus::Refundable
+        } else {
+            FundraiserStatus::Active
+        }
+    }
+
+    // @dev what ClaimVested would pay out at `now`
+    pub fn claimable(&self, now: i64) -> u64 {
+        self.raw
+            .vested_amount(now.max(0) as u64)
+            .saturating_sub(self.claimed_amount)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContributorView {
+    pub amount: u64,
+}
+
+impl ContributorView {
+    pub fn decode(data: &[u8]) -> Result<Self, FundraiserError> {
+        let state = bytemuck::try_from_bytes::<Contributor>(data)
+            .map_err(|_| FundraiserError::InvalidContributor)?;
+        Ok(Self {
+            amount: u64::from_le_bytes(state.amount),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllowlistEntryView {
+    pub fundraiser: Pubkey,
+    pub wallet: Pubkey,
+    pub bump: u8,
+}
+
+impl AllowlistEntryView {
+    pub fn decode(data: &[u8]) -> Result<Self, FundraiserError> {
+        let state = bytemuck::try_from_bytes::<AllowlistEntry>(data)
+            .map_err(|_| FundraiserError::NotAllowlisted)?;
+        Ok(Self {
+            fundraiser: Pubkey::new_from_array(state.fundraiser),
+            wallet: Pubkey::new_from_array(state.wallet),
+            bump: state.bump[0],
+        })
+    }
+}
+
+// Merkle allowlist
+
+// @dev builds the tree bottom-up, an odd node out is carried to the next level as is
+fn merkle_levels(wallets: &[Pubkey]) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![wallets
+        .iter()
+        .map(|wallet| merkle::leaf_hash(&wallet.to_bytes()))
+        .collect::<Vec<_>>()];
+
+    while levels.last().map_or(false, |level| level.len() > 1) {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => merkle::node_hash(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+
+    levels
+}
+
+pub fn merkle_root(wallets: &[Pubkey]) -> [u8; 32] {
+    merkle_levels(wallets)
+        .last()
+        .and_then(|level| level.first().copied())
+        .unwrap_or_default()
+}
+
+// @dev sibling hashes from the leaf up, `None` if `wallet` is not in the list
+pub fn merkle_proof(wallets: &[Pubkey], wallet: &Pubkey) -> Option<Vec<[u8; 32]>> {
+    let mut index = wallets.iter().position(|w| w == wallet)?;
+    let levels = merkle_levels(wallets);
+
+    let mut proof = Vec::new();
+    for level in &levels[..levels.len() - 1] {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        index /= 2;
+    }
+
+    Some(proof)
+}
+
+// Instructions
+
+pub fn initialize(
+    maker: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    args: InitializeInstruction,
+) -> Instruction {
+    let fundraiser = find_fundraiser_address(maker).0;
+
+    Instruction {
+        program_id: program_id(),
+        accounts: vec![
+            AccountMeta::new(*maker, true),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(fundraiser, false),
+            AccountMeta::new(associated_token_address(&fundraiser, mint, token_program), false),
+            AccountMeta::new(find_receipt_mint_address(&fundraiser).0, false),
+            AccountMeta::new_readonly(system_program_id(), false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(associated_token_program_id(), false),
+            AccountMeta::new_readonly(rent_sysvar_id(), false),
+        ],
+        data: [
+            vec![FundraiserInstructions::Initialize as u8],
+            args.to_bytes(),
+        ]
+        .concat(),
+    }
+}
+
+// @dev for ALLOWLIST_NONE fundraisers, see `contribute_allowlisted` and `contribute_with_proof`
+pub fn contribute(
+    contributor: &Pubkey,
+    maker: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    contribute_on_behalf(contributor, contributor, contributor, maker, mint, token_program, amount)
+}
+
+// @dev `payer` covers rent, `authority` sends the tokens and `beneficiary` is credited
+// with the contribution, the receipts and the refund right, gated fundraisers need
+// `authority` and `beneficiary` to be the same approved wallet
+pub fn contribute_on_behalf(
+    payer: &Pubkey,
+    authority: &Pubkey,
+    beneficiary: &Pubkey,
+    maker: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let fundraiser = find_fundraiser_address(maker).0;
+    let receipt_mint = find_receipt_mint_address(&fundraiser).0;
+
+    Instruction {
+        program_id: program_id(),
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(*beneficiary, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(fundraiser, false),
+            AccountMeta::new(associated_token_address(&fundraiser, mint, token_program), false),
+            AccountMeta::new(associated_token_address(authority, mint, token_program), false),
+            AccountMeta::new(find_contributor_address(beneficiary).0, false),
+            AccountMeta::new(receipt_mint, false),
+            AccountMeta::new(
+                associated_token_address(beneficiary, &receipt_mint, token_program),
+                false,
+            ),
+            AccountMeta::new_readonly(system_program_id(), false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(associated_token_program_id(), false),
+            AccountMeta::new_readonly(rent_sysvar_id(), false),
+        ],
+        data: [
+            vec![FundraiserInstructions::Contribute as u8],
+            amount.to_le_bytes().to_vec(),
+        ]
+        .concat(),
+    }
+}
+
+// @dev for wSOL campaigns, pays `amount` lamports straight from the contributor's wallet
+pub fn contribute_native(
+    contributor: &Pubkey,
+    maker: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut ix = contribute(contributor, maker, mint, token_program, amount);
+    ix.accounts[6] = AccountMeta::new(*contributor, true);
+    ix
+}
+
+// @dev for ALLOWLIST_PDA fundraisers
+pub fn contribute_allowlisted(
+    contributor: &Pubkey,
+    maker: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let fundraiser = find_fundraiser_address(maker).0;
+    let mut ix = contribute(contributor, maker, mint, token_program, amount);
+    ix.accounts.push(AccountMeta::new_readonly(
+        find_allowlist_entry_address(&fundraiser, contributor).0,
+        false,
+    ));
+    ix
+}
+
+// @dev for ALLOWLIST_MERKLE fundraisers, `proof` as returned by `merkle_proof`
+pub fn contribute_with_proof(
+    contributor: &Pubkey,
+    maker: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+    proof: &[[u8; 32]],
+) -> Instruction {
+    let mut ix = contribute(contributor, maker, mint, token_program, amount);
+    ix.data.extend(proof.concat());
+    ix
+}
+
+pub fn refund(contributor: &Pubkey, maker: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    let fundraiser = find_fundraiser_address(maker).0;
+    let receipt_mint = find_receipt_mint_address(&fundraiser).0;
+
+    Instruction {
+        program_id: program_id(),
+        accounts: vec![
+            AccountMeta::new(*contributor, true),
+            AccountMeta::new(*maker, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(fundraiser, false),
+            AccountMeta::new(associated_token_address(&fundraiser, mint, token_program), false),
+            AccountMeta::new(associated_token_address(contributor, mint, token_program), false),
+            AccountMeta::new(find_contributor_address(contributor).0, false),
+            AccountMeta::new(receipt_mint, false),
+            AccountMeta::new(
+                associated_token_address(contributor, &receipt_mint, token_program),
+                false,
+            ),
+            AccountMeta::new_readonly(system_program_id(), false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(associated_token_program_id(), false),
+            AccountMeta::new_readonly(rent_sysvar_id(), false),
+        ],
+        data: vec![FundraiserInstructions::Refund as u8],
+    }
+}
+
+// @dev for wSOL campaigns, pays the refund out as lamports to the contributor's wallet
+pub fn refund_native(
+    contributor: &Pubkey,
+    maker: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    let mut ix = refund(contributor, maker, mint, token_program);
+    ix.accounts[5] = AccountMeta::new(find_refund_account_address(contributor).0, false);
+    ix
+}
+
+pub fn check_contributions(maker: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    let fundraiser = find_fundraiser_address(maker).0;
+
+    Instruction {
+        program_id: program_id(),
+        accounts: vec![
+            AccountMeta::new(*maker, true),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(fundraiser, false),
+            AccountMeta::new(associated_token_address(&fundraiser, mint, token_program), false),
+            AccountMeta::new(associated_token_address(maker, mint, token_program), false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data: vec![FundraiserInstructions::CheckContributions as u8],
+    }
+}
+
+pub fn claim_vested(maker: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    Instruction {
+        data: vec![FundraiserInstructions::ClaimVested as u8],
+        ..check_contributions(maker, mint, token_program)
+    }
+}
+
+pub fn add_to_allowlist(maker: &Pubkey, wallet: &Pubkey) -> Instruction {
+    let fundraiser = find_fundraiser_address(maker).0;
+
+    Instruction {
+        program_id: program_id(),
+        accounts: vec![
+            AccountMeta::new(*maker, true),
+            AccountMeta::new_readonly(fundraiser, false),
+            AccountMeta::new_readonly(*wallet, false),
+            AccountMeta::new(find_allowlist_entry_address(&fundraiser, wallet).0, false),
+            AccountMeta::new_readonly(system_program_id(), false),
+        ],
+        data: vec![FundraiserInstructions::AddToAllowlist as u8],
+    }
+}
+
+pub fn remove_from_allowlist(maker: &Pubkey, wallet: &Pubkey) -> Instruction {
+    Instruction {
+        data: vec![FundraiserInstructions::RemoveFromAllowlist as u8],
+        ..add_to_allowlist(maker, wallet)
+    }
+}
@@ -0,0 +1,52 @@
+use pinocchio::program_error::ProgramError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum FundraiserError {
+    // campaign lifecycle
+    // @dev 0..=2 keep the codes refund returned before the errors were merged
+    TimeNotElapsed = 0,
+    TargetAlreadyReached = 1,
+    NoContribution = 2,
+    TargetNotReached = 3,
+
+    // account validation
+    InvalidFundraiser = 4,
+    InvalidMint = 5,
+    InvalidVault = 6,
+    InvalidReceiptMint = 7,
+    InvalidTokenAccount = 8,
+    InvalidContributor = 9,
+    InvalidTokenProgram = 10,
+
+    // instruction data validation
+    InvalidAmount = 11,
+    ContributionTooSmall = 12,
+    ContributionTooLarge = 13,
+    InsufficientBalance = 14,
+    Overflow = 15,
+    InvalidInstructionData = 16,
+
+    // stretch goals
+    HardCapReached = 17,
+    InvalidHardCap = 18,
+
+    // vesting
+    VestingEnabled = 19,
+    VestingDisabled = 20,
+    NothingToClaim = 21,
+
+    // allowlist
+    NotAllowlisted = 22,
+    InvalidAllowlistMode = 23,
+
+    // contribute
+    FundraiserEnded = 24,
+    VestingStarted = 25,
+}
+
+impl From<FundraiserError> for ProgramError {
+    fn from(e: FundraiserError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
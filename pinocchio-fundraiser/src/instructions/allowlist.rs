@@ -0,0 +1,111 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey,
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::{
+    error::FundraiserError,
+    state::{AllowlistEntry, Fundraiser, ALLOWLIST_PDA},
+};
+
+// @dev only the maker of a fundraiser in ALLOWLIST_PDA mode can manage its allowlist
+fn check_allowlist_authority(maker: &AccountInfo, fundraiser: &AccountInfo) -> ProgramResult {
+    if !maker.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !fundraiser.is_owned_by(&crate::ID) {
+        return Err(FundraiserError::InvalidFundraiser.into());
+    }
+    let data = fundraiser.try_borrow_data()?;
+    let state = Fundraiser::load(&data)?;
+    if &state.maker != maker.key() {
+        return Err(FundraiserError::InvalidFundraiser.into());
+    }
+    if state.allowlist_mode != [ALLOWLIST_PDA] {
+        return Err(FundraiserError::InvalidAllowlistMode.into());
+    }
+
+    Ok(())
+}
+
+// @dev maker approves `wallet` to contribute
+pub fn process_add_to_allowlist_instruction(accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
+    let [maker, fundraiser, wallet, allowlist_entry, _system_program, _others @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_allowlist_authority(maker, fundraiser)?;
+
+    let (allowlist_entry_pda, bump) = pubkey::find_program_address(
+        &[b"allowlist", fundraiser.key().as_ref(), wallet.key().as_ref()],
+        &crate::ID,
+    );
+    if allowlist_entry.key() != &allowlist_entry_pda {
+        return Err(FundraiserError::NotAllowlisted.into());
+    }
+
+    // @dev adding an approved wallet again is a no-op
+    if allowlist_entry.is_owned_by(&crate::ID) {
+        return Ok(());
+    }
+
+    let bump = [bump];
+    let seed = [
+        Seed::from(b"allowlist"),
+        Seed::from(fundraiser.key()),
+        Seed::from(wallet.key()),
+        Seed::from(&bump),
+    ];
+    CreateAccount {
+        from: maker,
+        lamports: Rent::get()?.minimum_balance(AllowlistEntry::LEN),
+        owner: &crate::ID,
+        space: AllowlistEntry::LEN as u64,
+        to: allowlist_entry,
+    }
+    .invoke_signed(&[Signer::from(&seed)])?;
+
+    let data = &mut allowlist_entry.try_borrow_mut_data()?;
+    let state = AllowlistEntry::load_mut(data)?;
+    state.fundraiser = *fundraiser.key();
+    state.wallet = *wallet.key();
+    state.bump = bump;
+
+    Ok(())
+}
+
+// @dev maker revokes `wallet`, rent goes back to the maker
+pub fn process_remove_from_allowlist_instruction(
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    let [maker, fundraiser, wallet, allowlist_entry, _others @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_allowlist_authority(maker, fundraiser)?;
+
+    if !allowlist_entry.is_owned_by(&crate::ID) {
+        return Err(FundraiserError::NotAllowlisted.into());
+    }
+    {
+        let data = allowlist_entry.try_borrow_data()?;
+        let state = AllowlistEntry::load(&data)?;
+        if &state.fundraiser != fundraiser.key() || &state.wallet != wallet.key() {
+            return Err(FundraiserError::NotAllowlisted.into());
+        }
+    }
+
+    // close the entry
+    *maker.try_borrow_mut_lamports()? += allowlist_entry.lamports();
+    *allowlist_entry.try_borrow_mut_lamports()? = 0;
+    allowlist_entry.close()?;
+
+    Ok(())
+}
@@ -4,28 +4,19 @@ use pinocchio::{
     program_error::ProgramError,
     pubkey, ProgramResult,
 };
-use pinocchio_token::{instructions::Transfer, state::TokenAccount};
 
-use crate::state::Fundraiser;
-
-#[derive(Debug, Clone, Copy)]
-#[repr(u32)]
-pub enum CheckContributionsError {
-    TargetNotReached = 0,
-}
-
-impl From<CheckContributionsError> for ProgramError {
-    fn from(e: CheckContributionsError) -> Self {
-        ProgramError::Custom(e as u32)
-    }
-}
+use crate::{
+    error::FundraiserError,
+    state::Fundraiser,
+    token_interface::{check_token_program, mint_state, token_account_state, TransferChecked},
+};
 
 // @dev maker can check contributions so far
 pub fn process_check_contributions_instruction(
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
-    let [maker, mint, fundraiser, vault, maker_ata, _others @ ..] = accounts else {
+    let [maker, mint, fundraiser, vault, maker_ata, token_program, _others @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
@@ -35,45 +26,53 @@ pub fn process_check_contributions_instruction(
 
     // Validate the Fundraiser account
     if fundraiser.owner() != &crate::ID {
-        return Err(ProgramError::InvalidAccountOwner);
+        return Err(FundraiserError::InvalidFundraiser.into());
     }
-    let fundraiser_data = fundraiser.try_borrow_data().unwrap();
-    let fundraiser_state =
-        bytemuck::try_pod_read_unaligned::<Fundraiser>(&fundraiser_data).unwrap();
+    let fundraiser_data = fundraiser.try_borrow_data()?;
+    let fundraiser_state = Fundraiser::load(&fundraiser_data)?;
     // Validating the mint
     if fundraiser_state.mint != *mint.key() {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(FundraiserError::InvalidMint.into());
+    }
+
+    // @dev vesting campaigns pay the maker through ClaimVested
+    if u64::from_le_bytes(fundraiser_state.vesting_duration) != 0 {
+        return Err(FundraiserError::VestingEnabled.into());
     }
 
+    // @dev mint can belong to either the legacy Token program or Token-2022
+    check_token_program(token_program, mint)?;
+
     // validating the vault owner
-    let vault_state = TokenAccount::from_account_info(vault)?;
+    let vault_state =
+        token_account_state(vault).map_err(|_| FundraiserError::InvalidVault)?;
     if vault_state.mint() != mint.key() {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(FundraiserError::InvalidVault.into());
     }
     if vault_state.owner() != fundraiser.key() {
-        return Err(ProgramError::InvalidAccountOwner);
+        return Err(FundraiserError::InvalidVault.into());
     }
 
     // Check if the target amount has been met
     let amount_to_raise = u64::from_le_bytes(fundraiser_state.amount_to_raise);
-    if vault_state.amount() >= amount_to_raise {
-        return Err(CheckContributionsError::TargetNotReached.into());
+    if vault_state.amount() < amount_to_raise {
+        return Err(FundraiserError::TargetNotReached.into());
     }
 
     // Validating the maker's token account
-    let maker_ata_state = TokenAccount::from_account_info(maker_ata)?;
+    let maker_ata_state = token_account_state(maker_ata)?;
     if maker_ata_state.mint() != mint.key() {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(FundraiserError::InvalidTokenAccount.into());
     }
     if maker_ata_state.owner() != maker.key() {
-        return Err(ProgramError::InvalidAccountOwner);
+        return Err(FundraiserError::InvalidTokenAccount.into());
     }
 
     // Validating the fundraiser account
-    let (fundraiser_pda, bump) =
+    let (fundraiser_pda, _bump) =
         pubkey::find_program_address(&[b"fundraiser", &maker.key().as_ref()], &crate::ID);
     if fundraiser.key() != &fundraiser_pda {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(FundraiserError::InvalidFundraiser.into());
     }
 
     // Define the fundraiser PDA
@@ -85,12 +84,20 @@ pub fn process_check_contributions_instruction(
     ];
     let signer = Signer::from(&signer_seeds);
 
+    let amount = vault_state.amount();
+    drop(vault_state);
+    drop(maker_ata_state);
+    let decimals = mint_state(mint)?.decimals();
+
     // Transfe the contributions to the maker
-    Transfer {
+    TransferChecked {
         from: vault,
+        mint,
         authority: fundraiser,
         to: maker_ata,
-        amount: vault_state.amount(),
+        amount,
+        decimals,
+        token_program: token_program.key(),
     }
     .invoke_signed(&[signer])?;
 
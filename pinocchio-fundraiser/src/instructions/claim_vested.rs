@@ -0,0 +1,110 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::FundraiserError,
+    state::Fundraiser,
+    token_interface::{check_token_program, mint_state, token_account_state, TransferChecked},
+};
+
+// @dev maker claims the part of the raised funds vested so far
+pub fn process_claim_vested_instruction(accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
+    let [maker, mint, fundraiser, vault, maker_ata, token_program, _others @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !maker.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // @dev mint can belong to either the legacy Token program or Token-2022
+    check_token_program(token_program, mint)?;
+
+    // Validating the fundraiser account
+    if fundraiser.owner() != &crate::ID {
+        return Err(FundraiserError::InvalidFundraiser.into());
+    }
+    let (fundraiser_pda, _bump) =
+        pubkey::find_program_address(&[b"fundraiser", maker.key().as_ref()], &crate::ID);
+    if fundraiser.key() != &fundraiser_pda {
+        return Err(FundraiserError::InvalidFundraiser.into());
+    }
+
+    // validating the vault owner
+    {
+        let vault_state =
+            token_account_state(vault).map_err(|_| FundraiserError::InvalidVault)?;
+        if vault_state.mint() != mint.key() || vault_state.owner() != fundraiser.key() {
+            return Err(FundraiserError::InvalidVault.into());
+        }
+    }
+
+    // Validating the maker's token account
+    {
+        let maker_ata_state = token_account_state(maker_ata)?;
+        if maker_ata_state.mint() != mint.key() || maker_ata_state.owner() != maker.key() {
+            return Err(FundraiserError::InvalidTokenAccount.into());
+        }
+    }
+
+    let (claimable, bump) = {
+        let data = &mut fundraiser.try_borrow_mut_data()?;
+        let state = Fundraiser::load_mut(data)?;
+
+        // Validating the mint
+        if state.mint != *mint.key() {
+            return Err(FundraiserError::InvalidMint.into());
+        }
+
+        // @dev campaigns without vesting pay the maker through CheckContributions
+        if u64::from_le_bytes(state.vesting_duration) == 0 {
+            return Err(FundraiserError::VestingDisabled.into());
+        }
+
+        if u64::from_le_bytes(state.goal_reached_at) == 0 {
+            return Err(FundraiserError::TargetNotReached.into());
+        }
+
+        // raised * elapsed / vesting_duration, minus what was already claimed
+        let now = Clock::get()?.unix_timestamp as u64;
+        let claimed = u64::from_le_bytes(state.claimed_amount);
+        let claimable = state.vested_amount(now).saturating_sub(claimed);
+        if claimable == 0 {
+            return Err(FundraiserError::NothingToClaim.into());
+        }
+
+        state.claimed_amount = claimed
+            .checked_add(claimable)
+            .ok_or(FundraiserError::Overflow)?
+            .to_le_bytes();
+
+        (claimable, state.bump)
+    };
+
+    let signer_seeds = [
+        Seed::from(b"fundraiser"),
+        Seed::from(maker.key().as_ref()),
+        Seed::from(&bump),
+    ];
+    let signer = Signer::from(&signer_seeds);
+
+    let decimals = mint_state(mint)?.decimals();
+    TransferChecked {
+        from: vault,
+        mint,
+        authority: fundraiser,
+        to: maker_ata,
+        amount: claimable,
+        decimals,
+        token_program: token_program.key(),
+    }
+    .invoke_signed(&[signer])?;
+
+    Ok(())
+}
@@ -2,142 +2,311 @@ use bytemuck::{Pod, Zeroable};
 
 
 use pinocchio::{
-    account_info::AccountInfo, instruction::{Account, Seed, Signer}, log::{self, sol_log_64}, msg, program_error::ProgramError, pubkey::{self, find_program_address, log}, sysvars::{self, rent::Rent, Sysvar}, ProgramResult
+    account_info::AccountInfo, instruction::{Account, Seed, Signer}, log::{self, sol_log_64}, msg, program_error::ProgramError, pubkey::{self, find_program_address, log}, sysvars::{self, clock::Clock, rent::Rent, Sysvar}, ProgramResult
 };
 // use pinocchio_log::log;
 use pinocchio_pubkey::derive_address;
-use pinocchio_system::instructions::CreateAccount;
-use pinocchio_token::instructions::Transfer;
+use pinocchio_system::instructions::{CreateAccount, Transfer};
 
-use crate::{fundraiser, state::{Contributor, Fundraiser}};
+use crate::{
+    error::FundraiserError,
+    fundraiser,
+    merkle,
+    state::{
+        AllowlistEntry, Contributor, Fundraiser, ALLOWLIST_MERKLE, ALLOWLIST_NONE, ALLOWLIST_PDA,
+    },
+    token_interface::{
+        check_token_program, is_native_mint, mint_state, token_account_state, MintTo, SyncNative,
+        TransferChecked,
+    },
+};
+
+// @dev ALLOWLIST_PDA expects the beneficiary's allowlist entry after the rent sysvar,
+// ALLOWLIST_MERKLE expects a proof after the amount in the instruction data
+fn check_allowlisted(
+    fundraiser_state: &Fundraiser,
+    fundraiser: &AccountInfo,
+    authority: &AccountInfo,
+    beneficiary: &AccountInfo,
+    allowlist_entry: Option<&AccountInfo>,
+    proof: &[u8],
+) -> ProgramResult {
+    // @dev gated fundraisers only take tokens from approved wallets, so an approved
+    // beneficiary has to send its own
+    if fundraiser_state.allowlist_mode[0] != ALLOWLIST_NONE && authority.key() != beneficiary.key() {
+        return Err(FundraiserError::NotAllowlisted.into());
+    }
+
+    match fundraiser_state.allowlist_mode[0] {
+        ALLOWLIST_NONE => Ok(()),
+        ALLOWLIST_PDA => {
+            let allowlist_entry = allowlist_entry.ok_or(FundraiserError::NotAllowlisted)?;
+            if !allowlist_entry.is_owned_by(&crate::ID) {
+                return Err(FundraiserError::NotAllowlisted.into());
+            }
+            let (allowlist_entry_pda, _bump) = find_program_address(
+                &[b"allowlist", fundraiser.key().as_ref(), beneficiary.key().as_ref()],
+                &crate::ID,
+            );
+            if allowlist_entry.key() != &allowlist_entry_pda {
+                return Err(FundraiserError::NotAllowlisted.into());
+            }
+            let data = allowlist_entry.try_borrow_data()?;
+            let state = AllowlistEntry::load(&data)?;
+            if &state.wallet != beneficiary.key() {
+                return Err(FundraiserError::NotAllowlisted.into());
+            }
+            Ok(())
+        }
+        ALLOWLIST_MERKLE => {
+            if !merkle::verify_proof(&fundraiser_state.merkle_root, beneficiary.key(), proof) {
+                return Err(FundraiserError::NotAllowlisted.into());
+            }
+            Ok(())
+        }
+        _ => Err(FundraiserError::InvalidAllowlistMode.into()),
+    }
+}
 
 
 pub fn process_contribute_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
-    let [contributor, mint, fundraiser, vault, contributor_ata, contributor_pda, system_program, token_program, associated_token_program, rent_sysvar @ ..] =
+    // @dev `payer` covers rent, `authority` owns the tokens being sent and `beneficiary`
+    // is credited with the contribution, the receipts and the refund right
+    let [payer, authority, beneficiary, mint, fundraiser, vault, authority_ata, contributor_pda, receipt_mint, beneficiary_receipt_ata, system_program, token_program, associated_token_program, rent_sysvar, remaining @ ..] =
         accounts
     else {
         return Err(pinocchio::program_error::ProgramError::NotEnoughAccountKeys);
     };
-    let amount = data;
+    // @dev amount, followed by the merkle proof for ALLOWLIST_MERKLE fundraisers
+    if data.len() < 8 {
+        return Err(FundraiserError::InvalidAmount.into());
+    }
+    let (amount, proof) = data.split_at(8);
+    let amount = u64::from_le_bytes(
+        amount.try_into()
+            .map_err(|_| FundraiserError::InvalidAmount)?,
+    );
 
-    // check that contributor is signer ✅
-    // check that maker is a signer ✅
-    if !&contributor.is_signer() {
+    // check that payer and token authority are signers ✅
+    if !payer.is_signer() || !authority.is_signer() {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
     // Validate that this program owns fundraiser 
     // @dev this can also be chacked using the seeds that derived the fundraiser
     if !&fundraiser.is_owned_by(&crate::ID) {
-        return Err(ProgramError::IllegalOwner);
+        return Err(FundraiserError::InvalidFundraiser.into());
     }
 
-    {
+    // @dev mint can belong to either the legacy Token program or Token-2022
+    check_token_program(token_program, mint)?;
+
+    // @dev backers of wSOL campaigns can pay in lamports by passing the authority's
+    // wallet in place of a token account, the vault wraps them
+    let native = is_native_mint(mint.key()) && authority_ata.key() == authority.key();
+
+    let (maker, fundraiser_bump, amount) = {
         // check that fundraiser exists 
-        let data = &mut fundraiser.try_borrow_mut_data()?;
-        let fundraiser_state = &mut bytemuck::from_bytes_mut::<Fundraiser>(data);
+        let data = fundraiser.try_borrow_data()?;
+        let fundraiser_state = Fundraiser::load(&data)?;
 
         // check that the mint is correct in fundraiser field ✅
         if mint.key() != &fundraiser_state.mint {
-            return Err(ProgramError::InvalidAccountData);
+            return Err(FundraiserError::InvalidMint.into());
+        }
+
+        // check that receipt mint is the one created with the fundraiser
+        if receipt_mint.key() != &fundraiser_state.receipt_mint {
+            return Err(FundraiserError::InvalidReceiptMint.into());
         }
 
+        // check that the tokens come from an approved wallet when the fundraiser is gated
+        check_allowlisted(fundraiser_state, fundraiser, authority, beneficiary, remaining.first(), proof)?;
+
         // check that provided vault is owned by fundraiser state
-        let vault_state = pinocchio_token::state::TokenAccount::from_account_info(&vault)?;
-        if vault_state.owner() != fundraiser.key() {
-            return Err(ProgramError::IllegalOwner);
+        let vault_state =
+            token_account_state(vault).map_err(|_| FundraiserError::InvalidVault)?;
+        if vault_state.owner() != fundraiser.key() || vault_state.mint() != mint.key() {
+            return Err(FundraiserError::InvalidVault.into());
         }
 
-        // check that contributor has enough amount to transfer
-        let contributor_ata_state =
-            pinocchio_token::state::TokenAccount::from_account_info(&*contributor_ata)?;
+        // check that the campaign is still running
+        // @dev refunds open at the deadline, a late contribution crossing the goal would
+        // set goal_reached_at and lock the remaining backers out of their refunds
+        let deadline = u64::from_le_bytes(fundraiser_state.time_started)
+            .checked_add(u64::from_le_bytes(fundraiser_state.duration))
+            .ok_or(FundraiserError::Overflow)?;
+        if Clock::get()?.unix_timestamp as u64 >= deadline {
+            return Err(FundraiserError::FundraiserEnded.into());
+        }
+
+        // @dev vesting pays out a share of current_amount from goal_reached_at, money
+        // arriving later would vest as if it had been there from the start
+        if u64::from_le_bytes(fundraiser_state.vesting_duration) != 0
+            && u64::from_le_bytes(fundraiser_state.goal_reached_at) != 0
+        {
+            return Err(FundraiserError::VestingStarted.into());
+        }
 
-        if contributor_ata_state.amount() < u64::from_le_bytes(amount.try_into().unwrap() ) {
-            return Err(ProgramError::InvalidArgument);
+        // @dev a contribution crossing the hard cap is clipped to the room left,
+        // the limits apply to what is actually sent
+        let remaining_room = fundraiser_state.remaining_room();
+        if remaining_room == 0 {
+            return Err(FundraiserError::HardCapReached.into());
         }
+        let amount = amount.min(remaining_room);
 
         // check that contributor is sending above minimum
-        if u64::from_le_bytes(amount.try_into().unwrap()) < fundraiser_state.min_sendable() {
-            return Err(ProgramError::InvalidArgument);  // amount less than minimum
+        // @dev unless it fills the last of the room, which may be less than the minimum
+        if amount < fundraiser_state.min_sendable() && amount != remaining_room {
+            return Err(FundraiserError::ContributionTooSmall.into());
         }
 
         // check that contributor is sending below maximum
-        if u64::from_le_bytes(amount.try_into().unwrap()) > fundraiser_state.max_sendable() {
-            return Err(ProgramError::InvalidArgument);
+        if amount > fundraiser_state.max_sendable() {
+            return Err(FundraiserError::ContributionTooLarge.into());
         }
-    }
+
+        // check that authority has enough amount to transfer
+        if native {
+            if authority.lamports() < amount {
+                return Err(FundraiserError::InsufficientBalance.into());
+            }
+        } else {
+            let authority_ata_state =
+                token_account_state(authority_ata)?;
+            if authority_ata_state.mint() != mint.key() {
+                return Err(FundraiserError::InvalidTokenAccount.into());
+            }
+
+            if authority_ata_state.amount() < amount {
+                return Err(FundraiserError::InsufficientBalance.into());
+            }
+        }
+
+        (fundraiser_state.maker, fundraiser_state.bump, amount)
+    };
 
 
     // @dev create contributor pda if it's not created already
     // @dev [init-if-needed]
-    let contributor_seeds: &[&[u8]] = &[b"contributor".as_ref(), contributor.key().as_ref()];
-
-    if contributor_pda.lamports() == 0 || contributor_pda.data_is_empty() {
-        let (contributor_pda_derived, bump) = find_program_address(&contributor_seeds, &crate::ID);
+    let contributor_seeds: &[&[u8]] = &[b"contributor".as_ref(), beneficiary.key().as_ref()];
+    let (contributor_pda_derived, bump) = find_program_address(&contributor_seeds, &crate::ID);
 
-        if contributor_pda.key() != &contributor_pda_derived{
-            return Err(ProgramError::InvalidAccountData);
-        }
+    if contributor_pda.key() != &contributor_pda_derived {
+        return Err(FundraiserError::InvalidContributor.into());
+    }
 
+    if contributor_pda.lamports() == 0 || contributor_pda.data_is_empty() {
         // create the account
         let bump = [bump.to_le()];
         let seed = [
             Seed::from(b"contributor"),
-            Seed::from(contributor.key()),
+            Seed::from(beneficiary.key()),
             Seed::from(&bump),
         ];
         let signer_seeds = Signer::from(&seed);
-        {
-            CreateAccount {
-                from: contributor,
-                lamports: Rent::get()?.minimum_balance(Contributor::LEN),
-                owner: &crate::ID,
-                space: Contributor::LEN as u64,
-                to: contributor_pda,
-            }
-            .invoke_signed(&[signer_seeds])?;
+        CreateAccount {
+            from: payer,
+            lamports: Rent::get()?.minimum_balance(Contributor::LEN),
+            owner: &crate::ID,
+            space: Contributor::LEN as u64,
+            to: contributor_pda,
         }
-        {
-            // deposit to the vault
-            Transfer {
-                amount: u64::from_le_bytes(amount.try_into().unwrap()),
-                authority: contributor,
-                from: contributor_ata,
-                to: vault,
-            }
-            .invoke()?;
+        .invoke_signed(&[signer_seeds])?;
+    } else if !contributor_pda.is_owned_by(&crate::ID) {
+        return Err(FundraiserError::InvalidContributor.into());
+    }
+
+    // deposit to the vault
+    // @dev with a transfer-fee mint the vault receives less than what was sent,
+    // so only what actually landed in the vault is credited
+    let vault_balance_before = token_account_state(vault)?.amount();
+    if native {
+        Transfer {
+            from: authority,
+            to: vault,
+            lamports: amount,
+        }
+        .invoke()?;
+
+        SyncNative {
+            native_token: vault,
+            token_program: token_program.key(),
+        }
+        .invoke()?;
+    } else {
+        let decimals = mint_state(mint)?.decimals();
+        TransferChecked {
+            amount,
+            authority,
+            from: authority_ata,
+            mint,
+            to: vault,
+            decimals,
+            token_program: token_program.key(),
         }
+        .invoke()?;
+    }
+    let received = token_account_state(vault)?
+        .amount()
+        .checked_sub(vault_balance_before)
+        .ok_or(FundraiserError::Overflow)?;
 
-        // increase contributor amount by how much was deposited
+    // increase raised amount, the first contribution to meet the goal stamps the time
+    {
+        let data = &mut fundraiser.try_borrow_mut_data()?;
+        let fundraiser_state = Fundraiser::load_mut(data)?;
 
-        let raw_account_data = &mut contributor_pda.try_borrow_mut_data()?;
-        let contributor_pda_state = bytemuck::from_bytes_mut::<Contributor>(raw_account_data);
+        let current_amount = u64::from_le_bytes(fundraiser_state.current_amount)
+            .checked_add(received)
+            .ok_or(FundraiserError::Overflow)?;
+        fundraiser_state.current_amount = current_amount.to_le_bytes();
 
-        contributor_pda_state.amount =
-            (u64::from_le_bytes(amount.try_into().unwrap()))
-            .to_le_bytes()
-    } else {
+        if u64::from_le_bytes(fundraiser_state.goal_reached_at) == 0
+            && current_amount >= u64::from_le_bytes(fundraiser_state.amount_to_raise)
+        {
+            fundraiser_state.goal_reached_at =
+                (Clock::get()?.unix_timestamp as u64).to_le_bytes();
+        }
+    }
+
+    // increase beneficiary's contributed amount by quantity being deposited
+    {
         let raw_account_data = &mut contributor_pda.try_borrow_mut_data()?;
-        let contributor_pda_state = bytemuck::from_bytes_mut::<Contributor>(raw_account_data);
+        let contributor_pda_state = Contributor::load_mut(raw_account_data)?;
 
-        contributor_pda_state.amount =
-            (u64::from_le_bytes(contributor_pda_state.amount)
-                + u64::from_le_bytes(amount.try_into().unwrap()))
+        contributor_pda_state.amount = u64::from_le_bytes(contributor_pda_state.amount)
+            .checked_add(received)
+            .ok_or(FundraiserError::Overflow)?
             .to_le_bytes();
+    }
 
-        // deposit to the vault
-        // increase contributor amount by quantity being deposited
-        {
-            // deposit to the vault
-            Transfer {
-                amount: u64::from_le_bytes(amount.try_into().unwrap()),
-                authority: contributor,
-                from: contributor_ata,
-                to: vault,
-            }
-            .invoke()?;
-        }
+    // @dev beneficiary receipt ata is created if it does not exist yet
+    pinocchio_associated_token_account::instructions::CreateIdempotent {
+        funding_account: payer,
+        account: beneficiary_receipt_ata,
+        wallet: beneficiary,
+        mint: receipt_mint,
+        system_program,
+        token_program,
+    }
+    .invoke()?;
+
+    // mint receipt tokens 1:1 with the credited amount
+    let seed = [
+        Seed::from(b"fundraiser"),
+        Seed::from(&maker),
+        Seed::from(&fundraiser_bump),
+    ];
+    MintTo {
+        mint: receipt_mint,
+        account: beneficiary_receipt_ata,
+        mint_authority: fundraiser,
+        amount: received,
+        token_program: token_program.key(),
     }
+    .invoke_signed(&[Signer::from(&seed)])?;
 
     Ok(())
 
@@ -15,16 +15,26 @@ use pinocchio::{
 // use pinocchio_log::log;
 use pinocchio_pubkey::derive_address;
 use pinocchio_system::instructions::CreateAccount;
+use pinocchio_token::state::Mint;
 
 use alloc::vec::Vec;
 
-use crate::{fundraiser, state::Fundraiser};
+use crate::{
+    error::FundraiserError,
+    fundraiser,
+    state::{Fundraiser, ALLOWLIST_MERKLE, ALLOWLIST_NONE, ALLOWLIST_PDA},
+    token_interface::{check_token_program, mint_state, token_account_state, InitializeMint2},
+};
 
 #[repr(C)]
 #[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
 pub struct InitializeInstruction {
     pub amount_to_raise: u64,
     pub duration: u64,
+    pub hard_cap: u64, // 0 for no hard cap
+    pub vesting_duration: u64, // 0 to pay out raised funds in one go
+    pub allowlist_mode: u64, // ALLOWLIST_NONE, ALLOWLIST_PDA or ALLOWLIST_MERKLE
+    pub merkle_root: [u8; 32], // only used with ALLOWLIST_MERKLE
 }
 
 
@@ -35,7 +45,7 @@ impl InitializeInstruction {
 }
 
 pub fn process_initialize_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
-    let [maker, mint, fundraiser, vault, system_program, token_program, associated_token_program, rent_sysvar @ ..] =
+    let [maker, mint, fundraiser, vault, receipt_mint, system_program, token_program, associated_token_program, rent_sysvar @ ..] =
 
         accounts
     else {
@@ -49,7 +59,20 @@ pub fn process_initialize_instruction(accounts: &[AccountInfo], data: &[u8]) ->
     }
 
     let ix_data = bytemuck::try_pod_read_unaligned::<InitializeInstruction>(data)
-        .map_err(|_| pinocchio::program_error::ProgramError::InvalidInstructionData)?;
+        .map_err(|_| FundraiserError::InvalidInstructionData)?;
+
+    // hard cap, when set, can not be below the goal
+    if ix_data.hard_cap != 0 && ix_data.hard_cap < ix_data.amount_to_raise {
+        return Err(FundraiserError::InvalidHardCap.into());
+    }
+
+    // merkle allowlist needs a root to check proofs against
+    let allowlist_mode = match ix_data.allowlist_mode {
+        0 => ALLOWLIST_NONE,
+        1 => ALLOWLIST_PDA,
+        2 if ix_data.merkle_root != [0u8; 32] => ALLOWLIST_MERKLE,
+        _ => return Err(FundraiserError::InvalidAllowlistMode.into()),
+    };
 
 
     let fundraiser_seeds = [b"fundraiser".as_ref(), maker.key().as_ref()];
@@ -57,25 +80,37 @@ pub fn process_initialize_instruction(accounts: &[AccountInfo], data: &[u8]) ->
 
     // Validate that derived == account 
     if fundraiser_pda != *fundraiser.key() {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(FundraiserError::InvalidFundraiser.into());
     }
 
-    let vault_state = pinocchio_token::state::TokenAccount::from_account_info(&vault)?;
+    // @dev mint can belong to either the legacy Token program or Token-2022
+    check_token_program(token_program, mint)?;
+
+    let vault_state = token_account_state(vault).map_err(|_| FundraiserError::InvalidVault)?;
     // Account fundraiser should be the authority over vault account 
     if vault_state.owner() != fundraiser.key() {
-        return Err(ProgramError::IllegalOwner);
+        return Err(FundraiserError::InvalidVault.into());
     }
 
     // check that mint is created ✅
-    let mint_state = pinocchio_token::state::Mint::from_account_info(&mint)?;
+    let mint_state = mint_state(mint)?;
     if !mint_state.is_initialized() {
-        return Err(ProgramError::UninitializedAccount);  // YO! mint does not exist
+        return Err(FundraiserError::InvalidMint.into());  // YO! mint does not exist
     }
     // check that vault mint is mint ✅
     if vault_state.mint() != mint.key() {
-        return Err(ProgramError::InvalidAccountData);    // "Yo!, You provided wrong mint address"
+        return Err(FundraiserError::InvalidVault.into());    // "Yo!, You provided wrong mint address"
+    }
+    let decimals = mint_state.decimals();
+    drop(mint_state);
+    drop(vault_state);
+
+    // receipt mint is a PDA of the fundraiser, the fundraiser is its mint authority
+    let (receipt_mint_pda, receipt_bump) =
+        pubkey::find_program_address(&[b"receipt".as_ref(), fundraiser.key().as_ref()], &crate::ID);
+    if receipt_mint_pda != *receipt_mint.key() {
+        return Err(FundraiserError::InvalidReceiptMint.into());
     }
-        
 
 
     // create fundraiser account
@@ -95,6 +130,31 @@ pub fn process_initialize_instruction(accounts: &[AccountInfo], data: &[u8]) ->
     }
     .invoke_signed(&[signer_seeds])?;
 
+    // create receipt mint account under the same token program as the mint
+    let receipt_bump = [receipt_bump.to_le()];
+    let receipt_seed = [
+        Seed::from(b"receipt"),
+        Seed::from(fundraiser.key()),
+        Seed::from(&receipt_bump),
+    ];
+    CreateAccount {
+        from: maker,
+        lamports: Rent::get()?.minimum_balance(Mint::LEN),
+        owner: token_program.key(),
+        space: Mint::LEN as u64,
+        to: receipt_mint,
+    }
+    .invoke_signed(&[Signer::from(&receipt_seed)])?;
+
+    InitializeMint2 {
+        mint: receipt_mint,
+        decimals,
+        mint_authority: fundraiser.key(),
+        freeze_authority: None,
+        token_program: token_program.key(),
+    }
+    .invoke()?;
+
     let data = &mut fundraiser.try_borrow_mut_data()?;
     let fundraiser_state = &mut bytemuck::from_bytes_mut::<Fundraiser>(data);
 
@@ -102,8 +162,15 @@ pub fn process_initialize_instruction(accounts: &[AccountInfo], data: &[u8]) ->
     fundraiser_state.bump = bump;
     fundraiser_state.current_amount = 0u64.to_le_bytes();
     fundraiser_state.duration = ix_data.duration.to_le_bytes();
+    fundraiser_state.hard_cap = ix_data.hard_cap.to_le_bytes();
+    fundraiser_state.goal_reached_at = 0u64.to_le_bytes();
+    fundraiser_state.vesting_duration = ix_data.vesting_duration.to_le_bytes();
+    fundraiser_state.claimed_amount = 0u64.to_le_bytes();
+    fundraiser_state.allowlist_mode = [allowlist_mode];
+    fundraiser_state.merkle_root = ix_data.merkle_root;
     fundraiser_state.maker = *maker.key();
     fundraiser_state.mint = *mint.key();
+    fundraiser_state.receipt_mint = *receipt_mint.key();
     fundraiser_state.time_started =
         (sysvars::clock::Clock::get()?.unix_timestamp as u64).to_le_bytes();
 
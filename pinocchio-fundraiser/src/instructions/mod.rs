@@ -1,9 +1,13 @@
+pub mod allowlist;
 pub mod check_contributions;
+pub mod claim_vested;
 pub mod contribute;
 pub mod initialize;
 pub mod refund;
 
+pub use allowlist::*;
 pub use check_contributions::*;
+pub use claim_vested::*;
 pub use contribute::*;
 pub use initialize::*;
 pub use refund::*;
@@ -14,12 +18,18 @@ pub enum FundraiserInstructions {
     Contribute = 1,
     CheckContributions = 2,
     Refund = 3,
+    ClaimVested = 4,
+    AddToAllowlist = 5,
+    RemoveFromAllowlist = 6,
 }
 
 // - intialize
 // - contribute
 // - check_contributions
 // - refund
+// - claim_vested
+// - add_to_allowlist
+// - remove_from_allowlist
 impl TryFrom<&u8> for FundraiserInstructions {
     type Error = pinocchio::program_error::ProgramError;
 
@@ -30,6 +40,9 @@ impl TryFrom<&u8> for FundraiserInstructions {
             2 => Ok(FundraiserInstructions::CheckContributions),
 
             3 => Ok(FundraiserInstructions::Refund),
+            4 => Ok(FundraiserInstructions::ClaimVested),
+            5 => Ok(FundraiserInstructions::AddToAllowlist),
+            6 => Ok(FundraiserInstructions::RemoveFromAllowlist),
             _ => Err(pinocchio::program_error::ProgramError::InvalidInstructionData),
         }
     }
@@ -2,30 +2,24 @@ use pinocchio::{
     account_info::AccountInfo,
     instruction::{Seed, Signer},
     program_error::ProgramError,
-    sysvars::{clock::Clock, Sysvar},
+    pubkey,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
     ProgramResult,
 };
+use pinocchio_system::instructions::{Allocate, Assign, CreateAccount, Transfer};
+use pinocchio_token::state::TokenAccount;
 
-use pinocchio_token::instructions::Transfer;
-
-use crate::state::{Contributor, Fundraiser};
-
-#[derive(Debug, Clone, Copy)]
-#[repr(u32)]
-pub enum RefundError {
-    TimeNotElapsed = 0,
-    TargetAlreadyReached = 1,
-    NoContribution = 2,
-}
-
-impl From<RefundError> for ProgramError {
-    fn from(e: RefundError) -> Self {
-        ProgramError::Custom(e as u32)
-    }
-}
+use crate::{
+    error::FundraiserError,
+    state::{Contributor, Fundraiser},
+    token_interface::{
+        check_token_program, is_native_mint, mint_state, token_account_state, Burn, CloseAccount,
+        InitializeAccount3, TransferChecked,
+    },
+};
 
 pub fn process_refund_instruction(accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
-    let [user, creator, mint, fundraiser, vault, contributor_ata, contributor_pda, _system_program, _token_program, _associated_token_program, _rent_sysvar @ ..] =
+    let [user, creator, mint, fundraiser, vault, contributor_ata, contributor_pda, receipt_mint, contributor_receipt_ata, _system_program, token_program, _associated_token_program, _rent_sysvar @ ..] =
         accounts
     else {
         return Err(pinocchio::program_error::ProgramError::NotEnoughAccountKeys);
@@ -36,59 +30,167 @@ pub fn process_refund_instruction(accounts: &[AccountInfo], _data: &[u8]) -> Pro
         return Err(ProgramError::MissingRequiredSignature);
     }
 
+    // @dev mint can belong to either the legacy Token program or Token-2022
+    check_token_program(token_program, mint)?;
+
     let bump = {
         // Fundraiser
-        let data = fundraiser.try_borrow_data().unwrap();
-        let state = bytemuck::try_pod_read_unaligned::<Fundraiser>(&data).unwrap();
+        if !fundraiser.is_owned_by(&crate::ID) {
+            return Err(FundraiserError::InvalidFundraiser.into());
+        }
+        let data = fundraiser.try_borrow_data()?;
+        let state = Fundraiser::load(&data)?;
+
+        // check that fundraiser was derived from the creator
+        if creator.key() != &state.maker {
+            return Err(FundraiserError::InvalidFundraiser.into());
+        }
+        let (fundraiser_pda, _bump) =
+            pubkey::find_program_address(&[b"fundraiser", creator.key().as_ref()], &crate::ID);
+        if fundraiser.key() != &fundraiser_pda {
+            return Err(FundraiserError::InvalidFundraiser.into());
+        }
 
         // check that duration has elapsed
         let clock = Clock::get();
         let current_time = clock?.unix_timestamp as u64;
-        if u64::from_le_bytes(state.duration) > current_time {
-            return Err(RefundError::TimeNotElapsed.into());
+        let deadline = u64::from_le_bytes(state.time_started)
+            .checked_add(u64::from_le_bytes(state.duration))
+            .ok_or(FundraiserError::Overflow)?;
+        if deadline > current_time {
+            return Err(FundraiserError::TimeNotElapsed.into());
         }
 
-        let vault_state = pinocchio_token::state::TokenAccount::from_account_info(vault).unwrap();
+        let vault_state =
+            token_account_state(vault).map_err(|_| FundraiserError::InvalidVault)?;
         // Target Already met ?
-        if u64::from_le_bytes(state.amount_to_raise) <= vault_state.amount() {
-            return Err(RefundError::TargetAlreadyReached.into());
+        // @dev tracked amount, the vault drains as vested funds are claimed
+        if u64::from_le_bytes(state.amount_to_raise) <= u64::from_le_bytes(state.current_amount) {
+            return Err(FundraiserError::TargetAlreadyReached.into());
+        }
+
+        // Validate fundraiser is owner of vault
+        if vault_state.owner() != fundraiser.key() {
+            return Err(FundraiserError::InvalidVault.into());
         }
 
-        // // Validate fundraiser is owner of vault
-        // if vault_state.owner() == fundraiser.key() {
-        //     return Err(ProgramError::IllegalOwner);
-        // }
+        // check that mint is the one the fundraiser was created with
+        if mint.key() != &state.mint {
+            return Err(FundraiserError::InvalidMint.into());
+        }
 
         // check that vault is of the mint
         if vault_state.mint() != mint.key() {
-            return Err(ProgramError::InvalidAccountData);
+            return Err(FundraiserError::InvalidVault.into());
+        }
+
+        // check that receipt mint is the one created with the fundraiser
+        if receipt_mint.key() != &state.receipt_mint {
+            return Err(FundraiserError::InvalidReceiptMint.into());
         }
 
         u8::from_le_bytes(state.bump)
     };
 
-    let contributor_amount = {
-        let data = contributor_pda.try_borrow_data().unwrap();
-        let state = bytemuck::try_pod_read_unaligned::<Contributor>(&data).unwrap();
-
-        // Ensure pda actually exists
-        if contributor_pda.data_is_empty() {
-            return Err(ProgramError::UninitializedAccount);
+    // @dev refund follows the receipts, whoever holds them can claim the refund
+    let refund_amount = {
+        let receipt_ata_state =
+            token_account_state(contributor_receipt_ata)?;
+        if receipt_ata_state.mint() != receipt_mint.key() {
+            return Err(FundraiserError::InvalidTokenAccount.into());
+        }
+        if receipt_ata_state.owner() != user.key() {
+            return Err(FundraiserError::InvalidTokenAccount.into());
         }
-        // Ensure contributor has deposited an amount
-        if u64::from_le_bytes(state.amount) == 0 {
-            return Err(RefundError::NoContribution.into());
+        // Ensure user holds receipts
+        if receipt_ata_state.amount() == 0 {
+            return Err(FundraiserError::NoContribution.into());
         }
 
-        state.amount
+        receipt_ata_state.amount()
     };
 
+    // @dev receipts only ever track what the vault received, this should never trip
+    if refund_amount > token_account_state(vault)?.amount() {
+        return Err(FundraiserError::InsufficientBalance.into());
+    }
+
+    // @dev wSOL refunds are paid out as native SOL through a temporary token account,
+    // the user's own wSOL ata and whatever it already holds are left alone
+    let native = is_native_mint(mint.key());
+    if native {
+        let (temp_account, temp_bump) =
+            pubkey::find_program_address(&[b"refund", user.key().as_ref()], &crate::ID);
+        if contributor_ata.key() != &temp_account {
+            return Err(FundraiserError::InvalidTokenAccount.into());
+        }
+
+        let temp_bump = [temp_bump];
+        let temp_seed = [
+            Seed::from(b"refund"),
+            Seed::from(user.key()),
+            Seed::from(&temp_bump),
+        ];
+        let space = TokenAccount::LEN as u64;
+        let lamports = Rent::get()?.minimum_balance(TokenAccount::LEN);
+        if contributor_ata.lamports() == 0 {
+            CreateAccount {
+                from: user,
+                to: contributor_ata,
+                lamports,
+                space,
+                owner: token_program.key(),
+            }
+            .invoke_signed(&[Signer::from(&temp_seed)])?;
+        } else {
+            // @dev lamports sent to the address beforehand must not block the refund
+            let top_up = lamports.saturating_sub(contributor_ata.lamports());
+            if top_up > 0 {
+                Transfer {
+                    from: user,
+                    to: contributor_ata,
+                    lamports: top_up,
+                }
+                .invoke()?;
+            }
+            Allocate {
+                account: contributor_ata,
+                space,
+            }
+            .invoke_signed(&[Signer::from(&temp_seed)])?;
+            Assign {
+                account: contributor_ata,
+                owner: token_program.key(),
+            }
+            .invoke_signed(&[Signer::from(&temp_seed)])?;
+        }
+
+        InitializeAccount3 {
+            account: contributor_ata,
+            mint,
+            owner: user.key(),
+            token_program: token_program.key(),
+        }
+        .invoke()?;
+    }
+
     {
         let contributor_ata_state =
-            pinocchio_token::state::TokenAccount::from_account_info(contributor_ata).unwrap();
+            token_account_state(contributor_ata)?;
         if contributor_ata_state.mint() != mint.key() {
-            return Err(ProgramError::InvalidAccountData);
+            return Err(FundraiserError::InvalidTokenAccount.into());
         }
+        // refund can only be paid out to the signer
+        if contributor_ata_state.owner() != user.key() {
+            return Err(FundraiserError::InvalidTokenAccount.into());
+        }
+    }
+
+    // check that contributor pda was derived from the signer
+    let (contributor_pda_derived, _bump) =
+        pubkey::find_program_address(&[b"contributor", user.key().as_ref()], &crate::ID);
+    if contributor_pda.key() != &contributor_pda_derived {
+        return Err(FundraiserError::InvalidContributor.into());
     }
 
     let bump = &[bump];
@@ -98,14 +200,62 @@ pub fn process_refund_instruction(accounts: &[AccountInfo], _data: &[u8]) -> Pro
         Seed::from(bump),
     ];
     let seeds = Signer::from(&seed);
+
+    // burn the receipts being redeemed
+    Burn {
+        account: contributor_receipt_ata,
+        mint: receipt_mint,
+        authority: user,
+        amount: refund_amount,
+        token_program: token_program.key(),
+    }
+    .invoke()?;
+
     // transfer amount back to contributro [refund]
-    Transfer {
-        amount: u64::from_le_bytes(contributor_amount),
+    let decimals = mint_state(mint)?.decimals();
+    TransferChecked {
+        amount: refund_amount,
         authority: fundraiser,
         from: vault,
+        mint,
         to: contributor_ata,
+        decimals,
+        token_program: token_program.key(),
     }
     .invoke_signed(&[seeds])?;
 
+    // unwrap the refund, closing the temporary account releases all its lamports
+    // including the rent the user just paid for it
+    if native {
+        CloseAccount {
+            account: contributor_ata,
+            destination: user,
+            authority: user,
+            token_program: token_program.key(),
+        }
+        .invoke()?;
+    }
+
+    // refunded tokens no longer count towards the raised amount
+    {
+        let data = &mut fundraiser.try_borrow_mut_data()?;
+        let state = Fundraiser::load_mut(data)?;
+        state.current_amount = u64::from_le_bytes(state.current_amount)
+            .saturating_sub(refund_amount)
+            .to_le_bytes();
+    }
+
+    // keep the contribution record in sync when the refund goes to the original contributor
+    if contributor_pda.is_owned_by(&crate::ID) && !contributor_pda.data_is_empty() {
+        if contributor_pda.data_len() != Contributor::LEN {
+            return Err(FundraiserError::InvalidContributor.into());
+        }
+        let data = &mut contributor_pda.try_borrow_mut_data()?;
+        let state = Contributor::load_mut(data)?;
+        state.amount = u64::from_le_bytes(state.amount)
+            .saturating_sub(refund_amount)
+            .to_le_bytes();
+    }
+
     Ok(())
 }
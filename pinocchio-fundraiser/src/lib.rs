@@ -1,11 +1,11 @@
-#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(any(test, feature = "client")), no_std)]
 use pinocchio::{
     account_info::AccountInfo, entrypoint, nostd_panic_handler, pubkey::Pubkey, ProgramResult,
 };
 
 use crate::instructions::*;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "client"))]
 extern crate std;
 
 extern crate alloc;
@@ -18,9 +18,16 @@ nostd_panic_handler!();
 #[cfg(test)]
 mod tests;
 
+#[cfg(feature = "client")]
+pub mod client;
+
+mod error;
 mod instructions;
+mod merkle;
 mod state;
+mod token_interface;
 
+pub use error::*;
 pub use instructions::*;
 pub use state::*;
 
@@ -34,7 +41,9 @@ pub fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    assert_eq!(program_id, &ID);
+    if program_id != &ID {
+        return Err(pinocchio::program_error::ProgramError::IncorrectProgramId);
+    }
 
     let (discriminator, data) = instruction_data
         .split_first()
@@ -47,6 +56,13 @@ pub fn process_instruction(
         FundraiserInstructions::CheckContributions => {
             process_check_contributions_instruction(accounts, data)?
         }
+        FundraiserInstructions::ClaimVested => process_claim_vested_instruction(accounts, data)?,
+        FundraiserInstructions::AddToAllowlist => {
+            process_add_to_allowlist_instruction(accounts, data)?
+        }
+        FundraiserInstructions::RemoveFromAllowlist => {
+            process_remove_from_allowlist_instruction(accounts, data)?
+        }
         _ => return Err(pinocchio::program_error::ProgramError::InvalidInstructionData),
     }
     Ok(())
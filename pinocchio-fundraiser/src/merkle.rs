@@ -0,0 +1,56 @@
+// @dev sorted-pair sha256 merkle tree over contributor wallets,
+// leaves and nodes are domain separated so a node can never pass as a leaf
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+pub fn hashv(vals: &[&[u8]]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+
+    #[cfg(target_os = "solana")]
+    unsafe {
+        pinocchio::syscalls::sol_sha256(
+            vals.as_ptr() as *const u8,
+            vals.len() as u64,
+            hash.as_mut_ptr(),
+        );
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        for val in vals {
+            hasher.update(val);
+        }
+        hash.copy_from_slice(&hasher.finalize());
+    }
+
+    hash
+}
+
+pub fn leaf_hash(wallet: &[u8; 32]) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, wallet])
+}
+
+pub fn node_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    if a <= b {
+        hashv(&[NODE_PREFIX, a, b])
+    } else {
+        hashv(&[NODE_PREFIX, b, a])
+    }
+}
+
+// @dev `proof` is the concatenation of the sibling hashes from the leaf up
+pub fn verify_proof(root: &[u8; 32], wallet: &[u8; 32], proof: &[u8]) -> bool {
+    if proof.len() % 32 != 0 {
+        return false;
+    }
+
+    let computed = proof.chunks_exact(32).fold(leaf_hash(wallet), |node, sibling| {
+        let mut sibling_hash = [0u8; 32];
+        sibling_hash.copy_from_slice(sibling);
+        node_hash(&node, &sibling_hash)
+    });
+
+    &computed == root
+}
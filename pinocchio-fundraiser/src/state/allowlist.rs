@@ -0,0 +1,35 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::program_error::ProgramError;
+
+use alloc::vec::Vec;
+
+use crate::FundraiserError;
+
+// @dev allowlist mode of a fundraiser
+pub const ALLOWLIST_NONE: u8 = 0;
+pub const ALLOWLIST_PDA: u8 = 1;
+pub const ALLOWLIST_MERKLE: u8 = 2;
+
+#[repr(C)]
+#[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
+pub struct AllowlistEntry {
+    pub fundraiser: [u8; 32],
+    pub wallet: [u8; 32],
+    pub bump: [u8; 1],
+}
+
+impl AllowlistEntry {
+    pub const LEN: usize = core::mem::size_of::<AllowlistEntry>();
+
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        bytemuck::try_from_bytes(data).map_err(|_| FundraiserError::NotAllowlisted.into())
+    }
+
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        bytemuck::try_from_bytes_mut(data).map_err(|_| FundraiserError::NotAllowlisted.into())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bytemuck::bytes_of(self).to_vec()
+    }
+}
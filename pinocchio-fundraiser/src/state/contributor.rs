@@ -1,5 +1,8 @@
 use bytemuck::{Pod, Zeroable};
 use alloc::vec::Vec;
+use pinocchio::program_error::ProgramError;
+
+use crate::FundraiserError;
 
 #[repr(C)]
 #[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
@@ -11,6 +14,14 @@ impl Contributor {
 
     pub const LEN: usize = core::mem::size_of::<Contributor>();
 
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        bytemuck::try_from_bytes(data).map_err(|_| FundraiserError::InvalidContributor.into())
+    }
+
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        bytemuck::try_from_bytes_mut(data).map_err(|_| FundraiserError::InvalidContributor.into())
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         bytemuck::bytes_of(self).to_vec()
     }
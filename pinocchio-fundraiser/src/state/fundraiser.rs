@@ -1,7 +1,10 @@
 use bytemuck::{Pod, Zeroable};
+use pinocchio::program_error::ProgramError;
 
 use alloc::vec::Vec;
 
+use crate::FundraiserError;
+
 #[repr(C)]
 #[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
 
@@ -13,11 +16,26 @@ pub struct Fundraiser {
     pub time_started: [u8; 8],
     pub duration: [u8; 8],
     pub bump: [u8; 1],
+    pub receipt_mint: [u8; 32],
+    pub hard_cap: [u8; 8],        // 0 when the campaign is uncapped
+    pub goal_reached_at: [u8; 8], // 0 until current_amount reaches amount_to_raise
+    pub vesting_duration: [u8; 8], // 0 when raised funds are paid out in one go
+    pub claimed_amount: [u8; 8],
+    pub allowlist_mode: [u8; 1], // ALLOWLIST_NONE, ALLOWLIST_PDA or ALLOWLIST_MERKLE
+    pub merkle_root: [u8; 32],
 }
 
 impl Fundraiser {
     pub const LEN: usize = core::mem::size_of::<Fundraiser>();
 
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        bytemuck::try_from_bytes(data).map_err(|_| FundraiserError::InvalidFundraiser.into())
+    }
+
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        bytemuck::try_from_bytes_mut(data).map_err(|_| FundraiserError::InvalidFundraiser.into())
+    }
+
     pub fn max_sendable(&self) -> u64 {
         10_000_000_000
     } // 10K usd
@@ -27,6 +45,30 @@ impl Fundraiser {
         10_000_000
     } // 10 usd
 
+    // @dev how much more the campaign can take before hitting the hard cap
+    pub fn remaining_room(&self) -> u64 {
+        match u64::from_le_bytes(self.hard_cap) {
+            0 => u64::MAX,
+            hard_cap => hard_cap.saturating_sub(u64::from_le_bytes(self.current_amount)),
+        }
+    }
+
+    // @dev linear vesting from the moment the goal was reached
+    pub fn vested_amount(&self, now: u64) -> u64 {
+        let goal_reached_at = u64::from_le_bytes(self.goal_reached_at);
+        let vesting_duration = u64::from_le_bytes(self.vesting_duration);
+        let raised = u64::from_le_bytes(self.current_amount);
+        if goal_reached_at == 0 {
+            return 0;
+        }
+        if vesting_duration == 0 {
+            return raised;
+        }
+
+        let elapsed = now.saturating_sub(goal_reached_at).min(vesting_duration);
+        (raised as u128 * elapsed as u128 / vesting_duration as u128) as u64
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
 
         bytemuck::bytes_of(self).to_vec()
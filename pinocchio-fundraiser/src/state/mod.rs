@@ -1,6 +1,7 @@
+pub mod allowlist;
 pub mod fundraiser;
 pub mod contributor;
 
+pub use allowlist::*;
 pub use fundraiser::*;
 pub use contributor::*;
-
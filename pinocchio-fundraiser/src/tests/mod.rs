@@ -16,7 +16,7 @@ mod tests {
     };
 
     use pinocchio_token::state::TokenAccount;
-    use solana_instruction::{AccountMeta, Instruction};
+    use solana_instruction::{error::InstructionError, AccountMeta, Instruction};
     use solana_keypair::Keypair;
     use solana_message::Message;
     use solana_native_token::LAMPORTS_PER_SOL;
@@ -24,9 +24,16 @@ mod tests {
     use solana_sdk_ids::system_program::ID as system_program_id;
     use solana_signer::Signer;
     use solana_transaction::Transaction;
-    use spl_associated_token_account::solana_program::clock::Clock;
+    use solana_transaction_error::TransactionError;
+    use spl_associated_token_account::{
+        get_associated_token_address_with_program_id, solana_program::clock::Clock,
+    };
+    use spl_token_2022::extension::{
+        transfer_fee::instruction::initialize_transfer_fee_config, ExtensionType,
+        StateWithExtensions,
+    };
 
-    use crate::{fundraiser, InitializeInstruction};
+    use crate::{fundraiser, FundraiserError, InitializeInstruction};
 
     const PROGRAM_ID: &str = "i7wWmYFi9LXizzU8p7b2iTm63qMftM4ykXoeqSxRKeV";
     const TOKEN_PROGRAM_ID: Pubkey = spl_token::ID;
@@ -59,6 +66,348 @@ mod tests {
         svm.send_transaction(transaction)
     }
 
+    // Helper: Send the transaction, the first signer pays the fees
+    fn send_multi_signed_tx(svm: &mut LiteSVM, ix: Instruction, signers: &[&Keypair]) -> TransactionResult {
+        let message = Message::new(&[ix], Some(&signers[0].pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+
+        let transaction = Transaction::new(signers, message, recent_blockhash);
+
+        svm.send_transaction(transaction)
+    }
+
+    fn get_receipt_mint(fundraiser: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"receipt".as_ref(), fundraiser.as_ref()], &get_program_id()).0
+    }
+
+    fn get_token_balance(svm: &LiteSVM, token_account: &Pubkey) -> u64 {
+        let account = svm.get_account(token_account).unwrap();
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+            .unwrap()
+            .base
+            .amount
+    }
+
+    fn initialize_ix(
+        maker: &Pubkey,
+        mint: &Pubkey,
+        fundraiser: &Pubkey,
+        vault: &Pubkey,
+        amount_to_raise: u64,
+        duration: u64,
+        token_program: &Pubkey,
+    ) -> Instruction {
+        let init_data = InitializeInstruction {
+            amount_to_raise,
+            duration,
+            hard_cap: 0,
+            vesting_duration: 0,
+            allowlist_mode: 0,
+            merkle_root: [0; 32],
+        };
+
+        initialize_ix_with(maker, mint, fundraiser, vault, init_data, token_program)
+    }
+
+    fn initialize_ix_with(
+        maker: &Pubkey,
+        mint: &Pubkey,
+        fundraiser: &Pubkey,
+        vault: &Pubkey,
+        init_data: InitializeInstruction,
+        token_program: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: get_program_id(),
+            accounts: vec![
+                AccountMeta::new(*maker, true),
+                AccountMeta::new(*mint, false),
+                AccountMeta::new(*fundraiser, false),
+                AccountMeta::new(*vault, false),
+                AccountMeta::new(get_receipt_mint(fundraiser), false),
+                AccountMeta::new(system_program_id, false),
+                AccountMeta::new(*token_program, false),
+                AccountMeta::new(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+                AccountMeta::new(Rent::id(), false),
+            ],
+            data: [
+                vec![crate::instructions::FundraiserInstructions::Initialize as u8],
+                init_data.to_bytes(),
+            ]
+            .concat(),
+        }
+    }
+
+    fn contribute_ix(
+        user: &Pubkey,
+        mint: &Pubkey,
+        fundraiser: &Pubkey,
+        vault: &Pubkey,
+        user_ata: &Pubkey,
+        amount: u64,
+        token_program: &Pubkey,
+    ) -> Instruction {
+        contribute_on_behalf_ix(user, user, user, mint, fundraiser, vault, user_ata, amount, token_program)
+    }
+
+    fn contribute_on_behalf_ix(
+        payer: &Pubkey,
+        authority: &Pubkey,
+        beneficiary: &Pubkey,
+        mint: &Pubkey,
+        fundraiser: &Pubkey,
+        vault: &Pubkey,
+        authority_ata: &Pubkey,
+        amount: u64,
+        token_program: &Pubkey,
+    ) -> Instruction {
+        let (contributor_pda, _bump) = Pubkey::find_program_address(
+            &[b"contributor".as_ref(), beneficiary.as_ref()],
+            &get_program_id(),
+        );
+        let receipt_mint = get_receipt_mint(fundraiser);
+        let beneficiary_receipt_ata =
+            get_associated_token_address_with_program_id(beneficiary, &receipt_mint, token_program);
+
+        Instruction {
+            program_id: get_program_id(),
+            accounts: vec![
+                AccountMeta::new(*payer, true),
+                AccountMeta::new(*authority, true),
+                AccountMeta::new_readonly(*beneficiary, false),
+                AccountMeta::new(*mint, false),
+                AccountMeta::new(*fundraiser, false),
+                AccountMeta::new(*vault, false),
+                AccountMeta::new(*authority_ata, false),
+                AccountMeta::new(contributor_pda, false),
+                AccountMeta::new(receipt_mint, false),
+                AccountMeta::new(beneficiary_receipt_ata, false),
+                AccountMeta::new_readonly(system_program_id, false),
+                AccountMeta::new_readonly(*token_program, false),
+                AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+                AccountMeta::new_readonly(Rent::id(), false),
+            ],
+            data: [
+                vec![crate::instructions::FundraiserInstructions::Contribute as u8],
+                amount.to_le_bytes().to_vec(),
+            ]
+            .concat(),
+        }
+    }
+
+    fn refund_ix(
+        user: &Pubkey,
+        maker: &Pubkey,
+        mint: &Pubkey,
+        fundraiser: &Pubkey,
+        vault: &Pubkey,
+        user_ata: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Instruction {
+        let (contributor_pda, _bump) = Pubkey::find_program_address(
+            &[b"contributor".as_ref(), user.as_ref()],
+            &get_program_id(),
+        );
+        let receipt_mint = get_receipt_mint(fundraiser);
+        let user_receipt_ata =
+            get_associated_token_address_with_program_id(user, &receipt_mint, token_program);
+
+        Instruction {
+            program_id: get_program_id(),
+            accounts: vec![
+                AccountMeta::new(*user, true),
+                AccountMeta::new(*maker, false),
+                AccountMeta::new(*mint, false),
+                AccountMeta::new(*fundraiser, false),
+                AccountMeta::new(*vault, false),
+                AccountMeta::new(*user_ata, false),
+                AccountMeta::new(contributor_pda, false),
+                AccountMeta::new(receipt_mint, false),
+                AccountMeta::new(user_receipt_ata, false),
+                AccountMeta::new_readonly(system_program_id, false),
+                AccountMeta::new_readonly(*token_program, false),
+                AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+                AccountMeta::new_readonly(Rent::id(), false),
+            ],
+            data: vec![crate::instructions::FundraiserInstructions::Refund as u8],
+        }
+    }
+
+    fn check_contributions_ix(
+        maker: &Pubkey,
+        mint: &Pubkey,
+        fundraiser: &Pubkey,
+        vault: &Pubkey,
+        maker_ata: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: get_program_id(),
+            accounts: vec![
+                AccountMeta::new(*maker, true),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(*fundraiser, false),
+                AccountMeta::new(*vault, false),
+                AccountMeta::new(*maker_ata, false),
+                AccountMeta::new_readonly(*token_program, false),
+            ],
+            data: vec![crate::instructions::FundraiserInstructions::CheckContributions as u8],
+        }
+    }
+
+    fn claim_vested_ix(
+        maker: &Pubkey,
+        mint: &Pubkey,
+        fundraiser: &Pubkey,
+        vault: &Pubkey,
+        maker_ata: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            data: vec![crate::instructions::FundraiserInstructions::ClaimVested as u8],
+            ..check_contributions_ix(maker, mint, fundraiser, vault, maker_ata, token_program)
+        }
+    }
+
+    fn get_allowlist_entry(fundraiser: &Pubkey, wallet: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"allowlist".as_ref(), fundraiser.as_ref(), wallet.as_ref()],
+            &get_program_id(),
+        )
+        .0
+    }
+
+    fn add_to_allowlist_ix(maker: &Pubkey, fundraiser: &Pubkey, wallet: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: get_program_id(),
+            accounts: vec![
+                AccountMeta::new(*maker, true),
+                AccountMeta::new_readonly(*fundraiser, false),
+                AccountMeta::new_readonly(*wallet, false),
+                AccountMeta::new(get_allowlist_entry(fundraiser, wallet), false),
+                AccountMeta::new_readonly(system_program_id, false),
+            ],
+            data: vec![crate::instructions::FundraiserInstructions::AddToAllowlist as u8],
+        }
+    }
+
+    fn remove_from_allowlist_ix(maker: &Pubkey, fundraiser: &Pubkey, wallet: &Pubkey) -> Instruction {
+        Instruction {
+            data: vec![crate::instructions::FundraiserInstructions::RemoveFromAllowlist as u8],
+            ..add_to_allowlist_ix(maker, fundraiser, wallet)
+        }
+    }
+
+    // Helper: Initialize a gated campaign and fund `user` with 1_000 tokens
+    fn setup_gated_campaign(
+        allowlist_mode: u64,
+        merkle_root: [u8; 32],
+        user: &Keypair,
+    ) -> (LiteSVM, Keypair, Pubkey, Pubkey, Pubkey, Pubkey) {
+        let (mut svm, maker, _, mint_authority, mint, _maker_ata, fundraiser, vault) = setup();
+
+        send_singed_tx(
+            &mut svm,
+            initialize_ix_with(
+                &maker.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                InitializeInstruction {
+                    amount_to_raise: 30_000_000,
+                    duration: 60,
+                    hard_cap: 0,
+                    vesting_duration: 0,
+                    allowlist_mode,
+                    merkle_root,
+                },
+                &TOKEN_PROGRAM_ID,
+            ),
+            &maker,
+        )
+        .map_err(|e| format!("[setup_gated_campaign] Initialize Transaction Failed: {:?}", e))
+        .unwrap();
+
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        let user_ata = CreateAssociatedTokenAccount::new(&mut svm, user, &mint)
+            .owner(&user.pubkey())
+            .send()
+            .unwrap();
+        MintTo::new(&mut svm, &mint_authority, &mint, &user_ata, 1_000_000_000)
+            .send()
+            .unwrap();
+
+        (svm, maker, mint, fundraiser, vault, user_ata)
+    }
+
+    // Helper: Assert the transaction failed with the given fundraiser error
+    fn assert_fundraiser_error(result: TransactionResult, expected: FundraiserError) {
+        let err = result
+            .map(|tx| format!("{:#?}", tx.logs))
+            .expect_err("Transaction should have failed");
+        assert_eq!(
+            err.err,
+            TransactionError::InstructionError(0, InstructionError::Custom(expected as u32)),
+            "Expected {:?}, logs: {:#?}",
+            expected,
+            err.meta.logs
+        );
+    }
+
+    // Helper: Initialize a campaign and fund a fresh contributor with 1_000 tokens
+    fn setup_campaign(
+        amount_to_raise: u64,
+        duration: u64,
+    ) -> (
+        LiteSVM,
+        Keypair,
+        Keypair,
+        Pubkey,
+        Pubkey,
+        Pubkey,
+        Pubkey,
+        Pubkey,
+    ) {
+        let (mut svm, maker, _, mint_authority, mint, maker_ata, fundraiser, vault) = setup();
+
+        send_singed_tx(
+            &mut svm,
+            initialize_ix(
+                &maker.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                amount_to_raise,
+                duration,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &maker,
+        )
+        .map_err(|e| format!("[setup_campaign] Initialize Transaction Failed: {:?}", e))
+        .unwrap();
+
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        let user_ata = CreateAssociatedTokenAccount::new(&mut svm, &user, &mint)
+            .owner(&user.pubkey())
+            .send()
+            .unwrap();
+        MintTo::new(&mut svm, &mint_authority, &mint, &user_ata, 1_000_000_000)
+            .send()
+            .unwrap();
+
+        (svm, maker, user, mint, maker_ata, fundraiser, vault, user_ata)
+    }
+
+    // Helper: Move the clock past the campaign deadline
+    fn warp_past(svm: &mut LiteSVM, duration: u64) {
+        let mut now = svm.get_sysvar::<Clock>();
+        now.unix_timestamp += duration as i64 + 1;
+        svm.set_sysvar::<Clock>(&now);
+    }
+
     fn setup() -> (
         LiteSVM,
         Keypair,
@@ -155,12 +504,17 @@ mod tests {
     #[test]
     pub fn test_initialize_instruction() {
         let (mut svm, maker, _, _, mint, _, fundraiser, vault) = setup();
+        let receipt_mint = get_receipt_mint(&fundraiser);
         let amount_to_raise: u64 = 4_000_000_000_000; // 4_000_000 tokens with 6 decimal places
         let duration: u64 = 2 * 7 * 24 * 60 * 60; // 2 weeks
 
         let init_data: InitializeInstruction = InitializeInstruction {
             amount_to_raise,
             duration,
+            hard_cap: 0,
+            vesting_duration: 0,
+            allowlist_mode: 0,
+            merkle_root: [0; 32],
         };
 
         let init_data_bytes = init_data.to_bytes();
@@ -178,6 +532,7 @@ mod tests {
                 AccountMeta::new(mint, false),
                 AccountMeta::new(fundraiser, false),
                 AccountMeta::new(vault, false),
+                AccountMeta::new(receipt_mint, false),
                 AccountMeta::new(system_program_id, false),
                 AccountMeta::new(TOKEN_PROGRAM_ID, false),
                 AccountMeta::new(ASSOCIATED_TOKEN_PROGRAM_ID, false),
@@ -221,6 +576,7 @@ mod tests {
     #[test]
     fn test_contribute_instruction() {
         let (mut svm, maker, owner, mint_authority, mint, maker_ata, fundraiser, vault) = setup();
+        let receipt_mint = get_receipt_mint(&fundraiser);
 
         // PRECOND
         {
@@ -230,6 +586,10 @@ mod tests {
             let init_data: InitializeInstruction = InitializeInstruction {
                 amount_to_raise,
                 duration,
+                hard_cap: 0,
+                vesting_duration: 0,
+                allowlist_mode: 0,
+                merkle_root: [0; 32],
             };
 
             let init_data_bytes = init_data.to_bytes();
@@ -247,6 +607,7 @@ mod tests {
                     AccountMeta::new(mint, false),
                     AccountMeta::new(fundraiser, false),
                     AccountMeta::new(vault, false),
+                    AccountMeta::new(receipt_mint, false),
                     AccountMeta::new(system_program_id, false),
                     AccountMeta::new(TOKEN_PROGRAM_ID, false),
                     AccountMeta::new(ASSOCIATED_TOKEN_PROGRAM_ID, false),
@@ -277,6 +638,11 @@ mod tests {
 
         msg!("[test_contribute_instruction] User ATA: {}\n", &user_ata);
 
+        let user_receipt_ata = spl_associated_token_account::get_associated_token_address(
+            &user.pubkey(),
+            &receipt_mint,
+        );
+
         MintTo::new(&mut svm, &mint_authority, &mint, &user_ata, 1_000_000_000)
             .send()
             .unwrap();
@@ -301,11 +667,15 @@ mod tests {
 
             accounts: vec![
                 AccountMeta::new(user.pubkey(), true),
+                AccountMeta::new(user.pubkey(), true),
+                AccountMeta::new_readonly(user.pubkey(), false),
                 AccountMeta::new(mint, false),
                 AccountMeta::new(fundraiser, false),
                 AccountMeta::new(vault, false),
                 AccountMeta::new(user_ata, false),
                 AccountMeta::new(contributor_pda, false),
+                AccountMeta::new(receipt_mint, false),
+                AccountMeta::new(user_receipt_ata, false),
                 AccountMeta::new_readonly(system_program_id, false),
                 AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
                 AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
@@ -324,11 +694,19 @@ mod tests {
 
         msg!("tx logs: {:#?}", tx.logs);
         msg!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        // POSTCOND
+        assert_eq!(
+            get_token_balance(&svm, &user_receipt_ata),
+            10_000_000,
+            "[test_contribute_instruction] Receipts should be minted 1:1 with the contribution"
+        );
     }
 
     #[test]
     fn test_refund_instruction() {
         let (mut svm, maker, owner, mint_authority, mint, maker_ata, fundraiser, vault) = setup();
+        let receipt_mint = get_receipt_mint(&fundraiser);
 
         // PRECOND
         let duration: u64 = 2 * 7 * 24 * 60 * 60; // 2 weeks
@@ -338,6 +716,10 @@ mod tests {
             let init_data: InitializeInstruction = InitializeInstruction {
                 amount_to_raise,
                 duration,
+                hard_cap: 0,
+                vesting_duration: 0,
+                allowlist_mode: 0,
+                merkle_root: [0; 32],
             };
 
             let init_data_bytes = init_data.to_bytes();
@@ -355,6 +737,7 @@ mod tests {
                     AccountMeta::new(mint, false),
                     AccountMeta::new(fundraiser, false),
                     AccountMeta::new(vault, false),
+                    AccountMeta::new(receipt_mint, false),
                     AccountMeta::new(system_program_id, false),
                     AccountMeta::new(TOKEN_PROGRAM_ID, false),
                     AccountMeta::new(ASSOCIATED_TOKEN_PROGRAM_ID, false),
@@ -385,6 +768,11 @@ mod tests {
 
         msg!("[test_contribute_instruction] User ATA: {}\n", &user_ata);
 
+        let user_receipt_ata = spl_associated_token_account::get_associated_token_address(
+            &user.pubkey(),
+            &receipt_mint,
+        );
+
         MintTo::new(&mut svm, &mint_authority, &mint, &user_ata, 1_000_000_000)
             .send()
             .unwrap();
@@ -410,11 +798,15 @@ mod tests {
 
                 accounts: vec![
                     AccountMeta::new(user.pubkey(), true),
+                    AccountMeta::new(user.pubkey(), true),
+                    AccountMeta::new_readonly(user.pubkey(), false),
                     AccountMeta::new(mint, false),
                     AccountMeta::new(fundraiser, false),
                     AccountMeta::new(vault, false),
                     AccountMeta::new(user_ata, false),
                     AccountMeta::new(contributor_pda, false),
+                    AccountMeta::new(receipt_mint, false),
+                    AccountMeta::new(user_receipt_ata, false),
                     AccountMeta::new_readonly(system_program_id, false),
                     AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
                     AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
@@ -455,6 +847,8 @@ mod tests {
                 AccountMeta::new(vault, false),
                 AccountMeta::new(user_ata, false),
                 AccountMeta::new(contributor_pda, false),
+                AccountMeta::new(receipt_mint, false),
+                AccountMeta::new(user_receipt_ata, false),
                 AccountMeta::new_readonly(system_program_id, false),
                 AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
                 AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
@@ -473,5 +867,1618 @@ mod tests {
 
         msg!("tx logs: {:#?}", tx.logs);
         msg!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        // POSTCOND
+        assert_eq!(
+            get_token_balance(&svm, &user_receipt_ata),
+            0,
+            "[test_refund_instruction] Receipts should be burnt on refund"
+        );
+        assert_eq!(
+            get_token_balance(&svm, &user_ata),
+            1_000_000_000,
+            "[test_refund_instruction] Contribution should be refunded"
+        );
+    }
+
+    #[test]
+    fn test_refund_with_transferred_receipts() {
+        let (mut svm, maker, _, mint_authority, mint, _, fundraiser, vault) = setup();
+        let receipt_mint = get_receipt_mint(&fundraiser);
+        let duration: u64 = 2 * 7 * 24 * 60 * 60; // 2 weeks
+
+        // PRECOND
+        send_singed_tx(
+            &mut svm,
+            initialize_ix(
+                &maker.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                4_000_000_000_000,
+                duration,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &maker,
+        )
+        .unwrap();
+
+        let user = Keypair::new();
+        let holder = Keypair::new();
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        svm.airdrop(&holder.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+
+        let user_ata = CreateAssociatedTokenAccount::new(&mut svm, &user, &mint)
+            .owner(&user.pubkey())
+            .send()
+            .unwrap();
+        let holder_ata = CreateAssociatedTokenAccount::new(&mut svm, &holder, &mint)
+            .owner(&holder.pubkey())
+            .send()
+            .unwrap();
+        MintTo::new(&mut svm, &mint_authority, &mint, &user_ata, 1_000_000_000)
+            .send()
+            .unwrap();
+
+        send_singed_tx(
+            &mut svm,
+            contribute_ix(
+                &user.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user_ata,
+                10_000_000,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &user,
+        )
+        .unwrap();
+
+        // Hand the receipts over to another wallet
+        let user_receipt_ata =
+            spl_associated_token_account::get_associated_token_address(&user.pubkey(), &receipt_mint);
+        let holder_receipt_ata = CreateAssociatedTokenAccount::new(&mut svm, &holder, &receipt_mint)
+            .owner(&holder.pubkey())
+            .send()
+            .unwrap();
+        let transfer_ix = spl_token::instruction::transfer(
+            &TOKEN_PROGRAM_ID,
+            &user_receipt_ata,
+            &holder_receipt_ata,
+            &user.pubkey(),
+            &[],
+            10_000_000,
+        )
+        .unwrap();
+        send_singed_tx(&mut svm, transfer_ix, &user).unwrap();
+
+        // time travel
+        let mut now = svm.get_sysvar::<Clock>();
+        now.unix_timestamp += duration as i64 + 1;
+        svm.set_sysvar::<Clock>(&now);
+
+        // The original contributor gave away the right to the refund
+        let result = send_singed_tx(
+            &mut svm,
+            refund_ix(
+                &user.pubkey(),
+                &maker.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user_ata,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &user,
+        );
+        assert_fundraiser_error(result, FundraiserError::NoContribution);
+
+        send_singed_tx(
+            &mut svm,
+            refund_ix(
+                &holder.pubkey(),
+                &maker.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &holder_ata,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &holder,
+        )
+        .map_err(|e| {
+            format!(
+                "[test_refund_with_transferred_receipts] Refund Transaction Failed: {:?}",
+                e
+            )
+        })
+        .unwrap();
+
+        // POSTCOND
+        assert_eq!(get_token_balance(&svm, &holder_ata), 10_000_000);
+        assert_eq!(get_token_balance(&svm, &holder_receipt_ata), 0);
+        assert_eq!(get_token_balance(&svm, &vault), 0);
+    }
+
+    #[test]
+    fn test_contribute_token_2022_transfer_fee() {
+        let mut svm = LiteSVM::new();
+        let maker = Keypair::new();
+        let user = Keypair::new();
+        let mint_authority = Keypair::new();
+        let token_program = spl_token_2022::ID;
+        let duration: u64 = 2 * 7 * 24 * 60 * 60; // 2 weeks
+        let fee_basis_points: u16 = 100; // 1%
+
+        svm.airdrop(&maker.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        svm.airdrop(&mint_authority.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+
+        let program_data =
+            std::fs::read(PathBuf::from("./target/sbpf-solana-solana/release/fundraiser.so"))
+                .expect("Failed to read program SO file");
+        svm.add_program(get_program_id(), &program_data);
+
+        // Token-2022 mint with a transfer-fee extension
+        let mint = Keypair::new();
+        {
+            let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(
+                &[ExtensionType::TransferFeeConfig],
+            )
+            .unwrap();
+            let create_ix = solana_system_interface::instruction::create_account(
+                &mint_authority.pubkey(),
+                &mint.pubkey(),
+                svm.minimum_balance_for_rent_exemption(space),
+                space as u64,
+                &token_program,
+            );
+            let fee_ix = initialize_transfer_fee_config(
+                &token_program,
+                &mint.pubkey(),
+                Some(&mint_authority.pubkey()),
+                Some(&mint_authority.pubkey()),
+                fee_basis_points,
+                u64::MAX,
+            )
+            .unwrap();
+            let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+                &token_program,
+                &mint.pubkey(),
+                &mint_authority.pubkey(),
+                None,
+                6,
+            )
+            .unwrap();
+            let tx = Transaction::new_signed_with_payer(
+                &[create_ix, fee_ix, init_mint_ix],
+                Some(&mint_authority.pubkey()),
+                &[&mint_authority, &mint],
+                svm.latest_blockhash(),
+            );
+            svm.send_transaction(tx).unwrap();
+        }
+        let mint = mint.pubkey();
+
+        let (fundraiser, _bump) = Pubkey::find_program_address(
+            &[b"fundraiser".as_ref(), maker.pubkey().as_ref()],
+            &get_program_id(),
+        );
+        let receipt_mint = get_receipt_mint(&fundraiser);
+        let vault = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&fundraiser)
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+        let user_ata = CreateAssociatedTokenAccount::new(&mut svm, &user, &mint)
+            .owner(&user.pubkey())
+            .token_program_id(&token_program)
+            .send()
+            .unwrap();
+        let mint_to_ix = spl_token_2022::instruction::mint_to(
+            &token_program,
+            &mint,
+            &user_ata,
+            &mint_authority.pubkey(),
+            &[],
+            1_000_000_000,
+        )
+        .unwrap();
+        send_singed_tx(&mut svm, mint_to_ix, &mint_authority).unwrap();
+
+        send_singed_tx(
+            &mut svm,
+            initialize_ix(
+                &maker.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                4_000_000_000_000,
+                duration,
+                &token_program,
+            ),
+            &maker,
+        )
+        .map_err(|e| {
+            format!(
+                "[test_contribute_token_2022_transfer_fee] Initialize Transaction Failed: {:?}",
+                e
+            )
+        })
+        .unwrap();
+
+        send_singed_tx(
+            &mut svm,
+            contribute_ix(
+                &user.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user_ata,
+                100_000_000,
+                &token_program,
+            ),
+            &user,
+        )
+        .map_err(|e| {
+            format!(
+                "[test_contribute_token_2022_transfer_fee] Contribute Transaction Failed: {:?}",
+                e
+            )
+        })
+        .unwrap();
+
+        // POSTCOND: only what reached the vault is credited
+        let received = 99_000_000;
+        let (contributor_pda, _bump) = Pubkey::find_program_address(
+            &[b"contributor".as_ref(), user.pubkey().as_ref()],
+            &get_program_id(),
+        );
+        let contributor_account = svm.get_account(&contributor_pda).unwrap();
+        let contributor_state =
+            bytemuck::try_from_bytes::<crate::state::Contributor>(&contributor_account.data)
+                .unwrap();
+        let user_receipt_ata = get_associated_token_address_with_program_id(
+            &user.pubkey(),
+            &receipt_mint,
+            &token_program,
+        );
+
+        assert_eq!(get_token_balance(&svm, &vault), received);
+        assert_eq!(u64::from_le_bytes(contributor_state.amount), received);
+        assert_eq!(get_token_balance(&svm, &user_receipt_ata), received);
+
+        // time travel
+        let mut now = svm.get_sysvar::<Clock>();
+        now.unix_timestamp += duration as i64 + 1;
+        svm.set_sysvar::<Clock>(&now);
+
+        send_singed_tx(
+            &mut svm,
+            refund_ix(
+                &user.pubkey(),
+                &maker.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user_ata,
+                &token_program,
+            ),
+            &user,
+        )
+        .map_err(|e| {
+            format!(
+                "[test_contribute_token_2022_transfer_fee] Refund Transaction Failed: {:?}",
+                e
+            )
+        })
+        .unwrap();
+
+        // POSTCOND: the refund drains exactly what the vault held
+        assert_eq!(get_token_balance(&svm, &vault), 0);
+        assert_eq!(get_token_balance(&svm, &user_receipt_ata), 0);
+    }
+
+    #[test]
+    fn test_initialize_malformed_data() {
+        let (mut svm, maker, _, _, mint, _, fundraiser, vault) = setup();
+
+        let mut init_ix = initialize_ix(
+            &maker.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            4_000_000_000_000,
+            60,
+            &TOKEN_PROGRAM_ID,
+        );
+        init_ix.data.truncate(9);
+
+        let result = send_singed_tx(&mut svm, init_ix, &maker);
+        assert_fundraiser_error(result, FundraiserError::InvalidInstructionData);
+    }
+
+    #[test]
+    fn test_contribute_malformed_amount() {
+        let (mut svm, _, user, mint, _, fundraiser, vault, user_ata) =
+            setup_campaign(4_000_000_000_000, 60);
+
+        let mut ix = contribute_ix(
+            &user.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &user_ata,
+            10_000_000,
+            &TOKEN_PROGRAM_ID,
+        );
+        ix.data.truncate(5);
+
+        let result = send_singed_tx(&mut svm, ix, &user);
+        assert_fundraiser_error(result, FundraiserError::InvalidAmount);
+    }
+
+    #[test]
+    fn test_contribute_amount_limits() {
+        let (mut svm, _, user, mint, _, fundraiser, vault, user_ata) =
+            setup_campaign(4_000_000_000_000, 60);
+
+        let cases = [
+            (1_000_000, FundraiserError::ContributionTooSmall),
+            (2_000_000_000, FundraiserError::InsufficientBalance),
+        ];
+        for (amount, expected) in cases {
+            let ix = contribute_ix(
+                &user.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user_ata,
+                amount,
+                &TOKEN_PROGRAM_ID,
+            );
+            let result = send_singed_tx(&mut svm, ix, &user);
+            assert_fundraiser_error(result, expected);
+        }
+
+        // the user can afford it, but it is above the per contribution cap
+        let mut user_account = svm.get_account(&user_ata).unwrap();
+        user_account.data[64..72].copy_from_slice(&20_000_000_000u64.to_le_bytes());
+        svm.set_account(user_ata, user_account).unwrap();
+
+        let ix = contribute_ix(
+            &user.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &user_ata,
+            11_000_000_000,
+            &TOKEN_PROGRAM_ID,
+        );
+        let result = send_singed_tx(&mut svm, ix, &user);
+        assert_fundraiser_error(result, FundraiserError::ContributionTooLarge);
+    }
+
+    #[test]
+    fn test_contribute_wrong_mint() {
+        let (mut svm, _, user, _, _, fundraiser, vault, user_ata) =
+            setup_campaign(4_000_000_000_000, 60);
+
+        let other_mint = CreateMint::new(&mut svm, &user)
+            .decimals(6)
+            .authority(&user.pubkey())
+            .send()
+            .unwrap();
+
+        let ix = contribute_ix(
+            &user.pubkey(),
+            &other_mint,
+            &fundraiser,
+            &vault,
+            &user_ata,
+            10_000_000,
+            &TOKEN_PROGRAM_ID,
+        );
+        let result = send_singed_tx(&mut svm, ix, &user);
+        assert_fundraiser_error(result, FundraiserError::InvalidMint);
+    }
+
+    #[test]
+    fn test_refund_before_deadline() {
+        let duration = 60;
+        let (mut svm, maker, user, mint, _, fundraiser, vault, user_ata) =
+            setup_campaign(4_000_000_000_000, duration);
+
+        send_singed_tx(
+            &mut svm,
+            contribute_ix(
+                &user.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user_ata,
+                10_000_000,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &user,
+        )
+        .unwrap();
+
+        let result = send_singed_tx(
+            &mut svm,
+            refund_ix(
+                &user.pubkey(),
+                &maker.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user_ata,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &user,
+        );
+        assert_fundraiser_error(result, FundraiserError::TimeNotElapsed);
+    }
+
+    #[test]
+    fn test_refund_after_target_reached() {
+        let duration = 60;
+        let (mut svm, maker, user, mint, _, fundraiser, vault, user_ata) =
+            setup_campaign(10_000_000, duration);
+
+        send_singed_tx(
+            &mut svm,
+            contribute_ix(
+                &user.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user_ata,
+                10_000_000,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &user,
+        )
+        .unwrap();
+        warp_past(&mut svm, duration);
+
+        let result = send_singed_tx(
+            &mut svm,
+            refund_ix(
+                &user.pubkey(),
+                &maker.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user_ata,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &user,
+        );
+        assert_fundraiser_error(result, FundraiserError::TargetAlreadyReached);
+    }
+
+    #[test]
+    fn test_check_contributions() {
+        let (mut svm, maker, user, mint, maker_ata, fundraiser, vault, user_ata) =
+            setup_campaign(20_000_000, 60);
+
+        let check_ix = check_contributions_ix(
+            &maker.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &maker_ata,
+            &TOKEN_PROGRAM_ID,
+        );
+
+        // vault is empty
+        let result = send_singed_tx(&mut svm, check_ix.clone(), &maker);
+        assert_fundraiser_error(result, FundraiserError::TargetNotReached);
+
+        send_singed_tx(
+            &mut svm,
+            contribute_ix(
+                &user.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user_ata,
+                20_000_000,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &user,
+        )
+        .unwrap();
+
+        let maker_balance = get_token_balance(&svm, &maker_ata);
+        send_singed_tx(&mut svm, check_ix, &maker)
+            .map_err(|e| format!("[test_check_contributions] Transaction Failed: {:?}", e))
+            .unwrap();
+
+        // POSTCOND
+        assert_eq!(get_token_balance(&svm, &vault), 0);
+        assert_eq!(get_token_balance(&svm, &maker_ata), maker_balance + 20_000_000);
+    }
+
+    #[test]
+    fn test_check_contributions_at_target() {
+        let (mut svm, maker, user, mint, maker_ata, fundraiser, vault, user_ata) =
+            setup_campaign(20_000_000, 60);
+        let check_ix = check_contributions_ix(
+            &maker.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &maker_ata,
+            &TOKEN_PROGRAM_ID,
+        );
+        let contribute = |amount: u64| {
+            contribute_ix(&user.pubkey(), &mint, &fundraiser, &vault, &user_ata, amount, &TOKEN_PROGRAM_ID)
+        };
+
+        // one short of the goal, the inverted `>=` check used to pay this out
+        send_singed_tx(&mut svm, contribute(19_999_999), &user).unwrap();
+        let result = send_singed_tx(&mut svm, check_ix.clone(), &maker);
+        assert_fundraiser_error(result, FundraiserError::TargetNotReached);
+
+        // exactly at the goal, which used to be rejected as not reached
+        svm.expire_blockhash();
+        send_singed_tx(&mut svm, contribute(1), &user).unwrap();
+        svm.expire_blockhash();
+        send_singed_tx(&mut svm, check_ix, &maker)
+            .map_err(|e| format!("[test_check_contributions_at_target] Transaction Failed: {:?}", e))
+            .unwrap();
+        assert_eq!(get_token_balance(&svm, &vault), 0);
+    }
+
+    #[test]
+    fn test_refund_deadline_counts_from_start() {
+        let duration = 60;
+        let (mut svm, maker, user, mint, _, fundraiser, vault, user_ata) =
+            setup_campaign(4_000_000_000_000, duration);
+
+        send_singed_tx(
+            &mut svm,
+            contribute_ix(&user.pubkey(), &mint, &fundraiser, &vault, &user_ata, 10_000_000, &TOKEN_PROGRAM_ID),
+            &user,
+        )
+        .unwrap();
+        let refund = refund_ix(
+            &user.pubkey(),
+            &maker.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &user_ata,
+            &TOKEN_PROGRAM_ID,
+        );
+
+        // the deadline used to be `duration` itself, which any real clock is already past
+        let time_started = {
+            let account = svm.get_account(&fundraiser).unwrap();
+            u64::from_le_bytes(crate::state::Fundraiser::load(&account.data).unwrap().time_started)
+        };
+        let mut now = svm.get_sysvar::<Clock>();
+        now.unix_timestamp = (time_started + duration) as i64 - 1;
+        svm.set_sysvar::<Clock>(&now);
+        let result = send_singed_tx(&mut svm, refund.clone(), &user);
+        assert_fundraiser_error(result, FundraiserError::TimeNotElapsed);
+
+        now.unix_timestamp += 1;
+        svm.set_sysvar::<Clock>(&now);
+        svm.expire_blockhash();
+        send_singed_tx(&mut svm, refund, &user)
+            .map_err(|e| format!("[test_refund_deadline_counts_from_start] Refund Failed: {:?}", e))
+            .unwrap();
+        assert_eq!(get_token_balance(&svm, &user_ata), 1_000_000_000);
+    }
+
+    #[test]
+    fn test_wrong_token_program() {
+        let (mut svm, maker, user, mint, maker_ata, fundraiser, vault, user_ata) =
+            setup_campaign(20_000_000, 60);
+
+        // used to surface as a generic IncorrectProgramId / InvalidAccountOwner
+        for token_program in [spl_token_2022::ID, Pubkey::new_unique()] {
+            let mut ix = contribute_ix(&user.pubkey(), &mint, &fundraiser, &vault, &user_ata, 10_000_000, &TOKEN_PROGRAM_ID);
+            ix.accounts[11].pubkey = token_program;
+            svm.expire_blockhash();
+            let result = send_singed_tx(&mut svm, ix, &user);
+            assert_fundraiser_error(result, FundraiserError::InvalidTokenProgram);
+
+            let mut ix = check_contributions_ix(&maker.pubkey(), &mint, &fundraiser, &vault, &maker_ata, &TOKEN_PROGRAM_ID);
+            ix.accounts[5].pubkey = token_program;
+            svm.expire_blockhash();
+            let result = send_singed_tx(&mut svm, ix, &maker);
+            assert_fundraiser_error(result, FundraiserError::InvalidTokenProgram);
+        }
+    }
+
+    #[test]
+    fn test_refund_rejects_foreign_accounts() {
+        let duration = 60;
+        let (mut svm, maker, user, mint, maker_ata, fundraiser, vault, user_ata) =
+            setup_campaign(4_000_000_000_000, duration);
+
+        // a second backer whose accounts the first one tries to redirect the refund through
+        let other = Keypair::new();
+        svm.airdrop(&other.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        let other_ata = CreateAssociatedTokenAccount::new(&mut svm, &other, &mint)
+            .owner(&other.pubkey())
+            .send()
+            .unwrap();
+        let mut other_account = svm.get_account(&other_ata).unwrap();
+        other_account.data[64..72].copy_from_slice(&1_000_000_000u64.to_le_bytes());
+        svm.set_account(other_ata, other_account).unwrap();
+
+        for (backer, backer_ata) in [(&user, user_ata), (&other, other_ata)] {
+            send_singed_tx(
+                &mut svm,
+                contribute_ix(
+                    &backer.pubkey(),
+                    &mint,
+                    &fundraiser,
+                    &vault,
+                    &backer_ata,
+                    10_000_000,
+                    &TOKEN_PROGRAM_ID,
+                ),
+                backer,
+            )
+            .unwrap();
+        }
+        warp_past(&mut svm, duration);
+
+        // a copy of the backer's account tagged as an extended mint rather than a token account
+        let mistyped_ata = Pubkey::new_unique();
+        let mut mistyped_account = svm.get_account(&user_ata).unwrap();
+        mistyped_account.data.resize(TokenAccount::LEN + 5, 0);
+        mistyped_account.data[TokenAccount::LEN] = 1;
+        svm.set_account(mistyped_ata, mistyped_account).unwrap();
+
+        let refund = refund_ix(
+            &user.pubkey(),
+            &maker.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &user_ata,
+            &TOKEN_PROGRAM_ID,
+        );
+        let other_refund = refund_ix(
+            &other.pubkey(),
+            &maker.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &other_ata,
+            &TOKEN_PROGRAM_ID,
+        );
+
+        // (account index, swapped in account, expected error)
+        let cases = [
+            (1, other.pubkey(), FundraiserError::InvalidFundraiser),
+            (4, maker_ata, FundraiserError::InvalidVault),
+            (5, other_ata, FundraiserError::InvalidTokenAccount),
+            (5, mistyped_ata, FundraiserError::InvalidTokenAccount),
+            (6, other_refund.accounts[6].pubkey, FundraiserError::InvalidContributor),
+            (8, other_refund.accounts[8].pubkey, FundraiserError::InvalidTokenAccount),
+        ];
+        for (index, account, expected) in cases {
+            let mut ix = refund.clone();
+            ix.accounts[index].pubkey = account;
+            svm.expire_blockhash();
+            let result = send_singed_tx(&mut svm, ix, &user);
+            assert_fundraiser_error(result, expected);
+        }
+
+        // untouched accounts still refund the signer only
+        svm.expire_blockhash();
+        send_singed_tx(&mut svm, refund, &user)
+            .map_err(|e| format!("[test_refund_rejects_foreign_accounts] Refund Failed: {:?}", e))
+            .unwrap();
+        assert_eq!(get_token_balance(&svm, &user_ata), 1_000_000_000);
+        assert_eq!(get_token_balance(&svm, &other_ata), 1_000_000_000 - 10_000_000);
+        assert_eq!(get_token_balance(&svm, &vault), 10_000_000);
+    }
+
+    #[test]
+    fn test_refund_rejects_other_fundraiser() {
+        let duration = 60;
+        let (mut svm, maker, user, mint, _, fundraiser, vault, user_ata) =
+            setup_campaign(4_000_000_000_000, duration);
+
+        // another maker runs a campaign on the same mint
+        let other_maker = Keypair::new();
+        svm.airdrop(&other_maker.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        let (other_fundraiser, _bump) = Pubkey::find_program_address(
+            &[b"fundraiser".as_ref(), other_maker.pubkey().as_ref()],
+            &get_program_id(),
+        );
+        let other_vault = CreateAssociatedTokenAccount::new(&mut svm, &other_maker, &mint)
+            .owner(&other_fundraiser)
+            .send()
+            .unwrap();
+        send_singed_tx(
+            &mut svm,
+            initialize_ix(
+                &other_maker.pubkey(),
+                &mint,
+                &other_fundraiser,
+                &other_vault,
+                4_000_000_000_000,
+                duration,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &other_maker,
+        )
+        .unwrap();
+
+        send_singed_tx(
+            &mut svm,
+            contribute_ix(
+                &user.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user_ata,
+                10_000_000,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &user,
+        )
+        .unwrap();
+        warp_past(&mut svm, duration);
+
+        // the creator does not match the fundraiser that signs for the vault
+        let mut ix = refund_ix(
+            &user.pubkey(),
+            &maker.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &user_ata,
+            &TOKEN_PROGRAM_ID,
+        );
+        ix.accounts[1].pubkey = other_maker.pubkey();
+        let result = send_singed_tx(&mut svm, ix, &user);
+        assert_fundraiser_error(result, FundraiserError::InvalidFundraiser);
+
+        // the other campaign's vault is not owned by this fundraiser
+        let mut ix = refund_ix(
+            &user.pubkey(),
+            &maker.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &user_ata,
+            &TOKEN_PROGRAM_ID,
+        );
+        ix.accounts[4].pubkey = other_vault;
+        let result = send_singed_tx(&mut svm, ix, &user);
+        assert_fundraiser_error(result, FundraiserError::InvalidVault);
+    }
+
+    #[test]
+    fn test_initialize_hard_cap_below_goal() {
+        let (mut svm, maker, _, _, mint, _, fundraiser, vault) = setup();
+
+        let mut init_ix = initialize_ix(
+            &maker.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            20_000_000,
+            60,
+            &TOKEN_PROGRAM_ID,
+        );
+        init_ix.data = [
+            vec![crate::instructions::FundraiserInstructions::Initialize as u8],
+            InitializeInstruction {
+                amount_to_raise: 20_000_000,
+                duration: 60,
+                hard_cap: 10_000_000,
+                vesting_duration: 0,
+                allowlist_mode: 0,
+                merkle_root: [0; 32],
+            }
+            .to_bytes(),
+        ]
+        .concat();
+
+        let result = send_singed_tx(&mut svm, init_ix, &maker);
+        assert_fundraiser_error(result, FundraiserError::InvalidHardCap);
+    }
+
+    #[test]
+    fn test_contribute_hard_cap() {
+        let (mut svm, maker, _, mint_authority, mint, _, fundraiser, vault) = setup();
+        let amount_to_raise: u64 = 20_000_000;
+        let hard_cap: u64 = 30_000_000;
+
+        let mut now = svm.get_sysvar::<Clock>();
+        now.unix_timestamp = 1_700_000_000;
+        svm.set_sysvar::<Clock>(&now);
+
+        let mut init_ix = initialize_ix(
+            &maker.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            amount_to_raise,
+            60,
+            &TOKEN_PROGRAM_ID,
+        );
+        init_ix.data = [
+            vec![crate::instructions::FundraiserInstructions::Initialize as u8],
+            InitializeInstruction {
+                amount_to_raise,
+                duration: 60,
+                hard_cap,
+                vesting_duration: 0,
+                allowlist_mode: 0,
+                merkle_root: [0; 32],
+            }
+            .to_bytes(),
+        ]
+        .concat();
+        send_singed_tx(&mut svm, init_ix, &maker).unwrap();
+
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        let user_ata = CreateAssociatedTokenAccount::new(&mut svm, &user, &mint)
+            .owner(&user.pubkey())
+            .send()
+            .unwrap();
+        MintTo::new(&mut svm, &mint_authority, &mint, &user_ata, 1_000_000_000)
+            .send()
+            .unwrap();
+        let contribute = |amount| {
+            contribute_ix(
+                &user.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user_ata,
+                amount,
+                &TOKEN_PROGRAM_ID,
+            )
+        };
+        let read_fundraiser = |svm: &LiteSVM| {
+            let account = svm.get_account(&fundraiser).unwrap();
+            *bytemuck::try_from_bytes::<crate::state::Fundraiser>(&account.data).unwrap()
+        };
+
+        // goal is met
+        send_singed_tx(&mut svm, contribute(amount_to_raise), &user).unwrap();
+        let state = read_fundraiser(&svm);
+        assert_eq!(u64::from_le_bytes(state.current_amount), amount_to_raise);
+        assert_eq!(u64::from_le_bytes(state.goal_reached_at), 1_700_000_000);
+
+        // stretch contribution is clipped to what is left under the hard cap
+        svm.expire_blockhash();
+        send_singed_tx(&mut svm, contribute(amount_to_raise), &user).unwrap();
+        let state = read_fundraiser(&svm);
+        assert_eq!(u64::from_le_bytes(state.current_amount), hard_cap);
+        assert_eq!(u64::from_le_bytes(state.goal_reached_at), 1_700_000_000);
+        assert_eq!(get_token_balance(&svm, &vault), hard_cap);
+        assert_eq!(get_token_balance(&svm, &user_ata), 1_000_000_000 - hard_cap);
+
+        // nothing more is accepted once the hard cap is reached
+        svm.expire_blockhash();
+        let result = send_singed_tx(&mut svm, contribute(amount_to_raise), &user);
+        assert_fundraiser_error(result, FundraiserError::HardCapReached);
+    }
+
+    #[test]
+    fn test_contribute_clipped_below_minimum_and_after_deadline() {
+        let (mut svm, maker, _, mint_authority, mint, _, fundraiser, vault) = setup();
+        // 5 tokens of room above the goal, less than the 10 token minimum
+        let amount_to_raise: u64 = 20_000_000;
+        let hard_cap: u64 = 25_000_000;
+        let duration: u64 = 60;
+
+        send_singed_tx(
+            &mut svm,
+            initialize_ix_with(
+                &maker.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                InitializeInstruction {
+                    amount_to_raise,
+                    duration,
+                    hard_cap,
+                    vesting_duration: 0,
+                    allowlist_mode: 0,
+                    merkle_root: [0; 32],
+                },
+                &TOKEN_PROGRAM_ID,
+            ),
+            &maker,
+        )
+        .unwrap();
+
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        let user_ata = CreateAssociatedTokenAccount::new(&mut svm, &user, &mint)
+            .owner(&user.pubkey())
+            .send()
+            .unwrap();
+        MintTo::new(&mut svm, &mint_authority, &mint, &user_ata, 1_000_000_000)
+            .send()
+            .unwrap();
+        let contribute = |amount| {
+            contribute_ix(&user.pubkey(), &mint, &fundraiser, &vault, &user_ata, amount, &TOKEN_PROGRAM_ID)
+        };
+
+        send_singed_tx(&mut svm, contribute(amount_to_raise), &user).unwrap();
+
+        // a dust contribution is still too small while it does not fill the room
+        svm.expire_blockhash();
+        let result = send_singed_tx(&mut svm, contribute(1_000_000), &user);
+        assert_fundraiser_error(result, FundraiserError::ContributionTooSmall);
+
+        // the last of the room is taken even though it is below the minimum
+        svm.expire_blockhash();
+        send_singed_tx(&mut svm, contribute(10_000_000), &user).unwrap();
+        assert_eq!(get_token_balance(&svm, &vault), hard_cap);
+
+        // a fresh campaign stops taking money at its deadline
+        let (mut svm, _, user, mint, _, fundraiser, vault, user_ata) =
+            setup_campaign(4_000_000_000_000, duration);
+        let time_started = {
+            let account = svm.get_account(&fundraiser).unwrap();
+            u64::from_le_bytes(crate::state::Fundraiser::load(&account.data).unwrap().time_started)
+        };
+        let mut now = svm.get_sysvar::<Clock>();
+        now.unix_timestamp = (time_started + duration) as i64;
+        svm.set_sysvar::<Clock>(&now);
+        let result = send_singed_tx(
+            &mut svm,
+            contribute_ix(&user.pubkey(), &mint, &fundraiser, &vault, &user_ata, 10_000_000, &TOKEN_PROGRAM_ID),
+            &user,
+        );
+        assert_fundraiser_error(result, FundraiserError::FundraiserEnded);
+    }
+
+    #[test]
+    fn test_claim_vested() {
+        let (mut svm, maker, _, mint_authority, mint, maker_ata, fundraiser, vault) = setup();
+        let amount_to_raise: u64 = 20_000_000;
+        let vesting_duration: u64 = 100;
+        let start: i64 = 1_700_000_000;
+
+        let mut now = svm.get_sysvar::<Clock>();
+        now.unix_timestamp = start;
+        svm.set_sysvar::<Clock>(&now);
+
+        send_singed_tx(
+            &mut svm,
+            initialize_ix_with(
+                &maker.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                InitializeInstruction {
+                    amount_to_raise,
+                    duration: 60,
+                    hard_cap: 0,
+                    vesting_duration,
+                    allowlist_mode: 0,
+                    merkle_root: [0; 32],
+                },
+                &TOKEN_PROGRAM_ID,
+            ),
+            &maker,
+        )
+        .unwrap();
+
+        let claim_ix = claim_vested_ix(
+            &maker.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &maker_ata,
+            &TOKEN_PROGRAM_ID,
+        );
+        let warp_to = |svm: &mut LiteSVM, offset: i64| {
+            let mut now = svm.get_sysvar::<Clock>();
+            now.unix_timestamp = start + offset;
+            svm.set_sysvar::<Clock>(&now);
+            svm.expire_blockhash();
+        };
+
+        // nothing vests before the goal is met
+        let result = send_singed_tx(&mut svm, claim_ix.clone(), &maker);
+        assert_fundraiser_error(result, FundraiserError::TargetNotReached);
+
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        let user_ata = CreateAssociatedTokenAccount::new(&mut svm, &user, &mint)
+            .owner(&user.pubkey())
+            .send()
+            .unwrap();
+        MintTo::new(&mut svm, &mint_authority, &mint, &user_ata, 1_000_000_000)
+            .send()
+            .unwrap();
+        send_singed_tx(
+            &mut svm,
+            contribute_ix(
+                &user.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user_ata,
+                amount_to_raise,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &user,
+        )
+        .unwrap();
+
+        // vesting campaigns can not be drained in one go
+        let result = send_singed_tx(
+            &mut svm,
+            check_contributions_ix(
+                &maker.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &maker_ata,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &maker,
+        );
+        assert_fundraiser_error(result, FundraiserError::VestingEnabled);
+
+        // the vesting schedule is fixed once it starts, later contributions are turned away
+        let result = send_singed_tx(
+            &mut svm,
+            contribute_ix(
+                &user.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user_ata,
+                10_000_000,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &user,
+        );
+        assert_fundraiser_error(result, FundraiserError::VestingStarted);
+
+        let maker_balance = get_token_balance(&svm, &maker_ata);
+        // (seconds since the goal was met, total paid to the maker so far)
+        let checkpoints = [
+            (25, 5_000_000),
+            (75, 15_000_000),
+            (100, 20_000_000),
+        ];
+        for (offset, total_claimed) in checkpoints {
+            warp_to(&mut svm, offset);
+            send_singed_tx(&mut svm, claim_ix.clone(), &maker)
+                .map_err(|e| format!("[test_claim_vested] Claim at +{}s Failed: {:?}", offset, e))
+                .unwrap();
+
+            let account = svm.get_account(&fundraiser).unwrap();
+            let state = bytemuck::try_from_bytes::<crate::state::Fundraiser>(&account.data).unwrap();
+            assert_eq!(u64::from_le_bytes(state.claimed_amount), total_claimed);
+            assert_eq!(get_token_balance(&svm, &maker_ata), maker_balance + total_claimed);
+            assert_eq!(get_token_balance(&svm, &vault), amount_to_raise - total_claimed);
+        }
+
+        // fully vested and claimed
+        warp_to(&mut svm, 500);
+        let result = send_singed_tx(&mut svm, claim_ix, &maker);
+        assert_fundraiser_error(result, FundraiserError::NothingToClaim);
+    }
+
+    #[test]
+    fn test_contribute_allowlist_pda() {
+        let user = Keypair::new();
+        let (mut svm, maker, mint, fundraiser, vault, user_ata) =
+            setup_gated_campaign(crate::state::ALLOWLIST_PDA as u64, [0; 32], &user);
+
+        let mut contribute = contribute_ix(
+            &user.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &user_ata,
+            10_000_000,
+            &TOKEN_PROGRAM_ID,
+        );
+        contribute
+            .accounts
+            .push(AccountMeta::new_readonly(get_allowlist_entry(&fundraiser, &user.pubkey()), false));
+
+        // not approved yet
+        let result = send_singed_tx(&mut svm, contribute.clone(), &user);
+        assert_fundraiser_error(result, FundraiserError::NotAllowlisted);
+
+        // only the maker can approve wallets
+        let result = send_singed_tx(
+            &mut svm,
+            add_to_allowlist_ix(&user.pubkey(), &fundraiser, &user.pubkey()),
+            &user,
+        );
+        assert_fundraiser_error(result, FundraiserError::InvalidFundraiser);
+
+        send_singed_tx(
+            &mut svm,
+            add_to_allowlist_ix(&maker.pubkey(), &fundraiser, &user.pubkey()),
+            &maker,
+        )
+        .map_err(|e| format!("[test_contribute_allowlist_pda] Add Transaction Failed: {:?}", e))
+        .unwrap();
+
+        svm.expire_blockhash();
+        send_singed_tx(&mut svm, contribute.clone(), &user)
+            .map_err(|e| format!("[test_contribute_allowlist_pda] Contribute Transaction Failed: {:?}", e))
+            .unwrap();
+        assert_eq!(get_token_balance(&svm, &vault), 10_000_000);
+
+        // a wallet that is not approved can not send its tokens in an approved one's name
+        let outsider = Keypair::new();
+        svm.airdrop(&outsider.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        let mut on_behalf = contribute_on_behalf_ix(
+            &outsider.pubkey(),
+            &outsider.pubkey(),
+            &user.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &user_ata,
+            10_000_000,
+            &TOKEN_PROGRAM_ID,
+        );
+        on_behalf
+            .accounts
+            .push(AccountMeta::new_readonly(get_allowlist_entry(&fundraiser, &user.pubkey()), false));
+        let result = send_singed_tx(&mut svm, on_behalf, &outsider);
+        assert_fundraiser_error(result, FundraiserError::NotAllowlisted);
+
+        // an entry approves its own wallet only
+        let mut foreign = contribute_ix(
+            &maker.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &user_ata,
+            10_000_000,
+            &TOKEN_PROGRAM_ID,
+        );
+        foreign
+            .accounts
+            .push(AccountMeta::new_readonly(get_allowlist_entry(&fundraiser, &user.pubkey()), false));
+        let result = send_singed_tx(&mut svm, foreign, &maker);
+        assert_fundraiser_error(result, FundraiserError::NotAllowlisted);
+
+        send_singed_tx(
+            &mut svm,
+            remove_from_allowlist_ix(&maker.pubkey(), &fundraiser, &user.pubkey()),
+            &maker,
+        )
+        .map_err(|e| format!("[test_contribute_allowlist_pda] Remove Transaction Failed: {:?}", e))
+        .unwrap();
+        assert!(svm
+            .get_account(&get_allowlist_entry(&fundraiser, &user.pubkey()))
+            .map_or(true, |account| account.lamports == 0));
+
+        svm.expire_blockhash();
+        let result = send_singed_tx(&mut svm, contribute, &user);
+        assert_fundraiser_error(result, FundraiserError::NotAllowlisted);
+    }
+
+    #[test]
+    fn test_contribute_allowlist_merkle() {
+        use crate::merkle::{leaf_hash, node_hash};
+
+        let user = Keypair::new();
+        let outsider = Keypair::new();
+        let leaves = [
+            leaf_hash(&user.pubkey().to_bytes()),
+            leaf_hash(&Keypair::new().pubkey().to_bytes()),
+            leaf_hash(&Keypair::new().pubkey().to_bytes()),
+            leaf_hash(&Keypair::new().pubkey().to_bytes()),
+        ];
+        let left = node_hash(&leaves[0], &leaves[1]);
+        let right = node_hash(&leaves[2], &leaves[3]);
+        let root = node_hash(&left, &right);
+
+        let (mut svm, _maker, mint, fundraiser, vault, user_ata) =
+            setup_gated_campaign(crate::state::ALLOWLIST_MERKLE as u64, root, &user);
+
+        let contribute_with_proof = |proof: &[[u8; 32]]| {
+            let mut ix = contribute_ix(
+                &user.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user_ata,
+                10_000_000,
+                &TOKEN_PROGRAM_ID,
+            );
+            ix.data.extend(proof.concat());
+            ix
+        };
+
+        // missing and wrong proofs
+        let result = send_singed_tx(&mut svm, contribute_with_proof(&[]), &user);
+        assert_fundraiser_error(result, FundraiserError::NotAllowlisted);
+        let result = send_singed_tx(&mut svm, contribute_with_proof(&[leaves[2], left]), &user);
+        assert_fundraiser_error(result, FundraiserError::NotAllowlisted);
+
+        send_singed_tx(&mut svm, contribute_with_proof(&[leaves[1], right]), &user)
+            .map_err(|e| format!("[test_contribute_allowlist_merkle] Contribute Transaction Failed: {:?}", e))
+            .unwrap();
+        assert_eq!(get_token_balance(&svm, &vault), 10_000_000);
+
+        // a valid proof does not carry over to another wallet
+        svm.airdrop(&outsider.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        let mut ix = contribute_ix(
+            &outsider.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &user_ata,
+            10_000_000,
+            &TOKEN_PROGRAM_ID,
+        );
+        ix.data.extend([leaves[1], right].concat());
+        let result = send_singed_tx(&mut svm, ix, &outsider);
+        assert_fundraiser_error(result, FundraiserError::NotAllowlisted);
+
+        // nor does it let another wallet send its tokens in the approved one's name
+        let mut on_behalf = contribute_on_behalf_ix(
+            &outsider.pubkey(),
+            &outsider.pubkey(),
+            &user.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &user_ata,
+            10_000_000,
+            &TOKEN_PROGRAM_ID,
+        );
+        on_behalf.data.extend([leaves[1], right].concat());
+        let result = send_singed_tx(&mut svm, on_behalf, &outsider);
+        assert_fundraiser_error(result, FundraiserError::NotAllowlisted);
+    }
+
+    #[test]
+    fn test_initialize_merkle_without_root() {
+        let (mut svm, maker, _, _, mint, _, fundraiser, vault) = setup();
+        let result = send_singed_tx(
+            &mut svm,
+            initialize_ix_with(
+                &maker.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                InitializeInstruction {
+                    amount_to_raise: 30_000_000,
+                    duration: 60,
+                    hard_cap: 0,
+                    vesting_duration: 0,
+                    allowlist_mode: crate::state::ALLOWLIST_MERKLE as u64,
+                    merkle_root: [0; 32],
+                },
+                &TOKEN_PROGRAM_ID,
+            ),
+            &maker,
+        );
+        assert_fundraiser_error(result, FundraiserError::InvalidAllowlistMode);
+    }
+
+    #[test]
+    #[cfg(feature = "client")]
+    fn test_client_views_and_builders() {
+        use crate::client::{self, FundraiserStatus, FundraiserView};
+
+        let (mut svm, maker, _, mint_authority, mint, _, fundraiser, vault) = setup();
+        let start = svm.get_sysvar::<Clock>().unix_timestamp;
+
+        // builders derive the same accounts the tests pass by hand
+        let init = client::initialize(
+            &maker.pubkey(),
+            &mint,
+            &TOKEN_PROGRAM_ID,
+            InitializeInstruction {
+                amount_to_raise: 40_000_000,
+                duration: 60,
+                hard_cap: 50_000_000,
+                vesting_duration: 0,
+                allowlist_mode: 0,
+                merkle_root: [0; 32],
+            },
+        );
+        assert_eq!(init.accounts[2].pubkey, fundraiser);
+        assert_eq!(init.accounts[3].pubkey, vault);
+        send_singed_tx(&mut svm, init, &maker).unwrap();
+
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        let user_ata = CreateAssociatedTokenAccount::new(&mut svm, &user, &mint)
+            .owner(&user.pubkey())
+            .send()
+            .unwrap();
+        MintTo::new(&mut svm, &mint_authority, &mint, &user_ata, 1_000_000_000)
+            .send()
+            .unwrap();
+        let contribute = client::contribute(
+            &user.pubkey(),
+            &maker.pubkey(),
+            &mint,
+            &TOKEN_PROGRAM_ID,
+            10_000_000,
+        );
+        assert_eq!(
+            contribute,
+            contribute_ix(&user.pubkey(), &mint, &fundraiser, &vault, &user_ata, 10_000_000, &TOKEN_PROGRAM_ID)
+        );
+        send_singed_tx(&mut svm, contribute, &user).unwrap();
+
+        let view = FundraiserView::decode(&svm.get_account(&fundraiser).unwrap().data).unwrap();
+        assert_eq!(view.address(), fundraiser);
+        assert_eq!(view.vault(&TOKEN_PROGRAM_ID), vault);
+        assert_eq!(view.current_amount, 10_000_000);
+        assert_eq!(view.percent_funded(), 25.0);
+        assert_eq!(view.remaining_room(), Some(40_000_000));
+        assert_eq!(view.deadline(), start + 60);
+        assert_eq!(view.time_remaining(start + 10), 50);
+        assert_eq!(view.status(start + 10), FundraiserStatus::Active);
+        assert_eq!(view.status(start + 60), FundraiserStatus::Refundable);
+
+        let (contributor_pda, _) = client::find_contributor_address(&user.pubkey());
+        let contributor = client::ContributorView::decode(&svm.get_account(&contributor_pda).unwrap().data).unwrap();
+        assert_eq!(contributor.amount, 10_000_000);
+
+        // the goal was missed, the refund builder pays the backer back
+        warp_past(&mut svm, 60);
+        let refund = client::refund(&user.pubkey(), &maker.pubkey(), &mint, &TOKEN_PROGRAM_ID);
+        assert_eq!(
+            refund,
+            refund_ix(&user.pubkey(), &maker.pubkey(), &mint, &fundraiser, &vault, &user_ata, &TOKEN_PROGRAM_ID)
+        );
+        send_singed_tx(&mut svm, refund, &user).unwrap();
+        assert_eq!(get_token_balance(&svm, &user_ata), 1_000_000_000);
+        let contributor = client::ContributorView::decode(&svm.get_account(&contributor_pda).unwrap().data).unwrap();
+        assert_eq!(contributor.amount, 0);
+
+        // proofs from the client verify on an odd sized list
+        let wallets: Vec<Pubkey> = (0..5).map(|_| Keypair::new().pubkey()).collect();
+        let root = client::merkle_root(&wallets);
+        for wallet in &wallets {
+            let proof = client::merkle_proof(&wallets, wallet).unwrap();
+            assert!(crate::merkle::verify_proof(&root, &wallet.to_bytes(), &proof.concat()));
+        }
+        assert!(client::merkle_proof(&wallets, &user.pubkey()).is_none());
+    }
+
+    #[test]
+    fn test_native_sol_contribute_and_refund() {
+        use litesvm_token::spl_token::{native_mint, solana_program::program_pack::Pack};
+
+        let mut svm = LiteSVM::new();
+        let maker = Keypair::new();
+        let user = Keypair::new();
+        svm.airdrop(&maker.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        let program_data = std::fs::read("./target/sbpf-solana-solana/release/fundraiser.so")
+            .expect("Failed to read program SO file");
+        svm.add_program(get_program_id(), &program_data);
+
+        let mint = native_mint::ID;
+        if svm.get_account(&mint).is_none() {
+            let mut data = vec![0; spl_token::state::Mint::LEN];
+            spl_token::state::Mint {
+                decimals: native_mint::DECIMALS,
+                is_initialized: true,
+                ..Default::default()
+            }
+            .pack_into_slice(&mut data);
+            svm.set_account(
+                mint,
+                solana_account::Account {
+                    lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+                    data,
+                    owner: TOKEN_PROGRAM_ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .unwrap();
+        }
+
+        let (fundraiser, _) = Pubkey::find_program_address(
+            &[b"fundraiser".as_ref(), maker.pubkey().as_ref()],
+            &get_program_id(),
+        );
+        let vault = CreateAssociatedTokenAccount::new(&mut svm, &maker, &mint)
+            .owner(&fundraiser)
+            .send()
+            .unwrap();
+        send_singed_tx(
+            &mut svm,
+            initialize_ix(&maker.pubkey(), &mint, &fundraiser, &vault, 5 * LAMPORTS_PER_SOL, 60, &TOKEN_PROGRAM_ID),
+            &maker,
+        )
+        .unwrap();
+
+        // the wallet itself stands in for the contributor's token account
+        send_singed_tx(
+            &mut svm,
+            contribute_ix(
+                &user.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &user.pubkey(),
+                2 * LAMPORTS_PER_SOL,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &user,
+        )
+        .map_err(|e| format!("[test_native_sol_contribute_and_refund] Contribute Transaction Failed: {:?}", e))
+        .unwrap();
+        assert_eq!(get_token_balance(&svm, &vault), 2 * LAMPORTS_PER_SOL);
+        let receipt_ata = get_associated_token_address_with_program_id(
+            &user.pubkey(),
+            &get_receipt_mint(&fundraiser),
+            &TOKEN_PROGRAM_ID,
+        );
+        assert_eq!(get_token_balance(&svm, &receipt_ata), 2 * LAMPORTS_PER_SOL);
+
+        // wSOL the user already holds must survive the refund
+        let user_ata = CreateAssociatedTokenAccount::new(&mut svm, &user, &mint)
+            .send()
+            .unwrap();
+        send_singed_tx(
+            &mut svm,
+            solana_system_interface::instruction::transfer(&user.pubkey(), &user_ata, LAMPORTS_PER_SOL),
+            &user,
+        )
+        .unwrap();
+        send_singed_tx(
+            &mut svm,
+            spl_token::instruction::sync_native(&TOKEN_PROGRAM_ID, &user_ata).unwrap(),
+            &user,
+        )
+        .unwrap();
+
+        // lamports sent to the temporary account beforehand must not block the refund
+        let (temp_account, _) =
+            Pubkey::find_program_address(&[b"refund".as_ref(), user.pubkey().as_ref()], &get_program_id());
+        svm.airdrop(&temp_account, 1_000).unwrap();
+
+        // refund lands back in the wallet as lamports, the temporary wSOL account is closed
+        warp_past(&mut svm, 60);
+        let lamports_before = svm.get_balance(&user.pubkey()).unwrap();
+        send_singed_tx(
+            &mut svm,
+            refund_ix(&user.pubkey(), &maker.pubkey(), &mint, &fundraiser, &vault, &temp_account, &TOKEN_PROGRAM_ID),
+            &user,
+        )
+        .map_err(|e| format!("[test_native_sol_contribute_and_refund] Refund Transaction Failed: {:?}", e))
+        .unwrap();
+
+        assert_eq!(get_token_balance(&svm, &vault), 0);
+        assert!(svm
+            .get_account(&temp_account)
+            .map_or(true, |account| account.lamports == 0));
+        assert_eq!(get_token_balance(&svm, &user_ata), LAMPORTS_PER_SOL);
+        let lamports_after = svm.get_balance(&user.pubkey()).unwrap();
+        assert!(lamports_after > lamports_before + 2 * LAMPORTS_PER_SOL - 10_000);
+        assert!(lamports_after <= lamports_before + 2 * LAMPORTS_PER_SOL + 1_000);
+
+    }
+
+    #[test]
+    fn test_contribute_on_behalf() {
+        let amount_to_raise = 30_000_000;
+        let (mut svm, maker, sponsor, mint, _, fundraiser, vault, sponsor_ata) =
+            setup_campaign(amount_to_raise, 60);
+        let relayer = Keypair::new();
+        let backer = Keypair::new();
+        svm.airdrop(&relayer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+        svm.airdrop(&backer.pubkey(), LAMPORTS_PER_SOL)
+            .expect("Airdrop failed");
+
+        // the sponsor's tokens fund the backer, the relayer pays rent and fees
+        let sponsor_lamports = svm.get_balance(&sponsor.pubkey()).unwrap();
+        send_multi_signed_tx(
+            &mut svm,
+            contribute_on_behalf_ix(
+                &relayer.pubkey(),
+                &sponsor.pubkey(),
+                &backer.pubkey(),
+                &mint,
+                &fundraiser,
+                &vault,
+                &sponsor_ata,
+                10_000_000,
+                &TOKEN_PROGRAM_ID,
+            ),
+            &[&relayer, &sponsor],
+        )
+        .map_err(|e| format!("[test_contribute_on_behalf] Contribute Transaction Failed: {:?}", e))
+        .unwrap();
+
+        assert_eq!(svm.get_balance(&sponsor.pubkey()).unwrap(), sponsor_lamports);
+        assert_eq!(get_token_balance(&svm, &sponsor_ata), 1_000_000_000 - 10_000_000);
+        assert_eq!(get_token_balance(&svm, &vault), 10_000_000);
+
+        // contribution and receipts are credited to the backer, not the sponsor
+        let (backer_pda, _) = Pubkey::find_program_address(
+            &[b"contributor".as_ref(), backer.pubkey().as_ref()],
+            &get_program_id(),
+        );
+        let account = svm.get_account(&backer_pda).unwrap();
+        let state = bytemuck::try_from_bytes::<crate::state::Contributor>(&account.data).unwrap();
+        assert_eq!(u64::from_le_bytes(state.amount), 10_000_000);
+        let (sponsor_pda, _) = Pubkey::find_program_address(
+            &[b"contributor".as_ref(), sponsor.pubkey().as_ref()],
+            &get_program_id(),
+        );
+        assert!(svm.get_account(&sponsor_pda).is_none());
+        let receipt_mint = get_receipt_mint(&fundraiser);
+        let backer_receipt_ata =
+            get_associated_token_address_with_program_id(&backer.pubkey(), &receipt_mint, &TOKEN_PROGRAM_ID);
+        assert_eq!(get_token_balance(&svm, &backer_receipt_ata), 10_000_000);
+
+        // the token authority has to sign
+        let mut unsigned = contribute_on_behalf_ix(
+            &relayer.pubkey(),
+            &sponsor.pubkey(),
+            &backer.pubkey(),
+            &mint,
+            &fundraiser,
+            &vault,
+            &sponsor_ata,
+            10_000_000,
+            &TOKEN_PROGRAM_ID,
+        );
+        unsigned.accounts[1].is_signer = false;
+        let result = send_singed_tx(&mut svm, unsigned, &relayer);
+        assert_eq!(
+            result.unwrap_err().err,
+            TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+        );
+
+        // the refund goes to the backer
+        warp_past(&mut svm, 60);
+        let backer_ata = CreateAssociatedTokenAccount::new(&mut svm, &backer, &mint)
+            .owner(&backer.pubkey())
+            .send()
+            .unwrap();
+        send_singed_tx(
+            &mut svm,
+            refund_ix(&backer.pubkey(), &maker.pubkey(), &mint, &fundraiser, &vault, &backer_ata, &TOKEN_PROGRAM_ID),
+            &backer,
+        )
+        .map_err(|e| format!("[test_contribute_on_behalf] Refund Transaction Failed: {:?}", e))
+        .unwrap();
+        assert_eq!(get_token_balance(&svm, &backer_ata), 10_000_000);
+        assert_eq!(get_token_balance(&svm, &vault), 0);
     }
 }
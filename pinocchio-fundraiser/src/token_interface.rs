@@ -0,0 +1,301 @@
+use pinocchio::{
+    account_info::{AccountInfo, Ref},
+    cpi::invoke_signed,
+    instruction::{AccountMeta, Instruction, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+use pinocchio_token::state::{Mint, TokenAccount};
+
+use crate::FundraiserError;
+
+// @dev `pinocchio_token` instructions are hardwired to the legacy Token program,
+// these mirror them but take the token program that owns the mint.
+
+pub const TOKEN_2022_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+// @dev wrapped SOL mints of the legacy Token program and Token-2022
+pub const NATIVE_MINT: Pubkey =
+    pinocchio_pubkey::pubkey!("So11111111111111111111111111111111111111112");
+pub const NATIVE_MINT_2022: Pubkey =
+    pinocchio_pubkey::pubkey!("9pan9bMn5HatX4EJdBwg9VgCa7Uz5HL8N1m5D3NdXTPp");
+
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    mint == &NATIVE_MINT || mint == &NATIVE_MINT_2022
+}
+
+pub fn is_token_program(program_id: &Pubkey) -> bool {
+    program_id == &pinocchio_token::ID || program_id == &TOKEN_2022_PROGRAM_ID
+}
+
+// @dev `token_program` must be the program owning `mint`
+pub fn check_token_program(token_program: &AccountInfo, mint: &AccountInfo) -> ProgramResult {
+    if !is_token_program(token_program.key()) || !mint.is_owned_by(token_program.key()) {
+        return Err(FundraiserError::InvalidTokenProgram.into());
+    }
+    Ok(())
+}
+
+// @dev Token-2022 accounts with extensions carry their type right after the
+// token account layout, mints are padded up to it
+const ACCOUNT_TYPE_OFFSET: usize = TokenAccount::LEN;
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+// @dev reads the base mint state, Token-2022 extensions are ignored
+pub fn mint_state(mint: &AccountInfo) -> Result<Ref<Mint>, ProgramError> {
+    if !is_token_program(mint.owner()) {
+        return Err(FundraiserError::InvalidMint.into());
+    }
+    let data = mint.try_borrow_data()?;
+    if data.len() < Mint::LEN {
+        return Err(FundraiserError::InvalidMint.into());
+    }
+    if data.len() > Mint::LEN && data.get(ACCOUNT_TYPE_OFFSET) != Some(&ACCOUNT_TYPE_MINT) {
+        return Err(FundraiserError::InvalidMint.into());
+    }
+    Ok(Ref::map(data, |data| unsafe {
+        Mint::from_bytes_unchecked(&data[..Mint::LEN])
+    }))
+}
+
+// @dev reads the base token account state, Token-2022 extensions are ignored
+pub fn token_account_state(account: &AccountInfo) -> Result<Ref<TokenAccount>, ProgramError> {
+    if !is_token_program(account.owner()) {
+        return Err(FundraiserError::InvalidTokenAccount.into());
+    }
+    let data = account.try_borrow_data()?;
+    if data.len() < TokenAccount::LEN {
+        return Err(FundraiserError::InvalidTokenAccount.into());
+    }
+    if data.len() > TokenAccount::LEN && data[ACCOUNT_TYPE_OFFSET] != ACCOUNT_TYPE_ACCOUNT {
+        return Err(FundraiserError::InvalidTokenAccount.into());
+    }
+    Ok(Ref::map(data, |data| unsafe {
+        TokenAccount::from_bytes_unchecked(&data[..TokenAccount::LEN])
+    }))
+}
+
+pub struct TransferChecked<'a> {
+    pub from: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub to: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub amount: u64,
+    pub decimals: u8,
+    pub token_program: &'a Pubkey,
+}
+
+impl TransferChecked<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.from.key()),
+            AccountMeta::readonly(self.mint.key()),
+            AccountMeta::writable(self.to.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        // discriminator (1) + amount (8) + decimals (1)
+        let mut data = [0u8; 10];
+        data[0] = 12;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        data[9] = self.decimals;
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.from, self.mint, self.to, self.authority],
+            signers,
+        )
+    }
+}
+
+pub struct MintTo<'a> {
+    pub mint: &'a AccountInfo,
+    pub account: &'a AccountInfo,
+    pub mint_authority: &'a AccountInfo,
+    pub amount: u64,
+    pub token_program: &'a Pubkey,
+}
+
+impl MintTo<'_> {
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.mint.key()),
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::readonly_signer(self.mint_authority.key()),
+        ];
+
+        // discriminator (1) + amount (8)
+        let mut data = [0u8; 9];
+        data[0] = 7;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.mint, self.account, self.mint_authority],
+            signers,
+        )
+    }
+}
+
+pub struct Burn<'a> {
+    pub account: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub amount: u64,
+    pub token_program: &'a Pubkey,
+}
+
+impl Burn<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::writable(self.mint.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        // discriminator (1) + amount (8)
+        let mut data = [0u8; 9];
+        data[0] = 8;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.account, self.mint, self.authority],
+            &[],
+        )
+    }
+}
+
+pub struct InitializeMint2<'a> {
+    pub mint: &'a AccountInfo,
+    pub decimals: u8,
+    pub mint_authority: &'a Pubkey,
+    pub freeze_authority: Option<&'a Pubkey>,
+    pub token_program: &'a Pubkey,
+}
+
+impl InitializeMint2<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas = [AccountMeta::writable(self.mint.key())];
+
+        // discriminator (1) + decimals (1) + mint_authority (32) + option (1) + freeze_authority (32)
+        let mut data = [0u8; 67];
+        data[0] = 20;
+        data[1] = self.decimals;
+        data[2..34].copy_from_slice(self.mint_authority);
+        if let Some(freeze_authority) = self.freeze_authority {
+            data[34] = 1;
+            data[35..67].copy_from_slice(freeze_authority);
+        }
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke_signed(&instruction, &[self.mint], &[])
+    }
+}
+
+pub struct InitializeAccount3<'a> {
+    pub account: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub owner: &'a Pubkey,
+    pub token_program: &'a Pubkey,
+}
+
+impl InitializeAccount3<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::readonly(self.mint.key()),
+        ];
+
+        // discriminator (1) + owner (32)
+        let mut data = [0u8; 33];
+        data[0] = 18;
+        data[1..33].copy_from_slice(self.owner);
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke_signed(&instruction, &[self.account, self.mint], &[])
+    }
+}
+
+pub struct SyncNative<'a> {
+    pub native_token: &'a AccountInfo,
+    pub token_program: &'a Pubkey,
+}
+
+impl SyncNative<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas = [AccountMeta::writable(self.native_token.key())];
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &[17],
+        };
+
+        invoke_signed(&instruction, &[self.native_token], &[])
+    }
+}
+
+pub struct CloseAccount<'a> {
+    pub account: &'a AccountInfo,
+    pub destination: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub token_program: &'a Pubkey,
+}
+
+impl CloseAccount<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::writable(self.destination.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &[9],
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.account, self.destination, self.authority],
+            &[],
+        )
+    }
+}
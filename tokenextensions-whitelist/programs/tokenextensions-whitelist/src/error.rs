@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum WhitelistError {
+    #[msg("Signer is not the mint's transfer hook authority")]
+    NotTransferHookAuthority,
+    #[msg("Signer is not the whitelist admin")]
+    NotAdmin,
+    #[msg("Signer is not the pending admin")]
+    NotPendingAdmin,
+    #[msg("TransferHook: Owner is not whitelisted")]
+    OwnerNotWhitelisted,
+    #[msg("TransferHook: Not transferring")]
+    NotTransferring,
+    #[msg("Minting an initial supply needs the creator's token account")]
+    MissingUserTokenAccount,
+    #[msg("TransferHook: Whitelist entry is not valid yet")]
+    WhitelistNotYetValid,
+    #[msg("TransferHook: Whitelist entry has expired")]
+    WhitelistExpired,
+    #[msg("valid_until must be after valid_from, or 0 for no expiry")]
+    InvalidValidity,
+    #[msg("Legacy whitelist entry is not a whitelist account")]
+    InvalidLegacyEntry,
+    #[msg("TransferHook: Amount is over the per-transfer limit")]
+    TransferAmountTooLarge,
+    #[msg("TransferHook: Daily limit exceeded")]
+    DailyLimitExceeded,
+    #[msg("Signer is not the program's upgrade authority")]
+    NotUpgradeAuthority,
+}
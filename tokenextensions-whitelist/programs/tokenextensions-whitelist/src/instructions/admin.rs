@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::spl_token_2022::extension::transfer_hook::TransferHook as TransferHookExtension,
+    token_interface::{get_mint_extension_data, Mint},
+};
+
+use crate::{error::WhitelistError, states::HookConfig};
+
+/// Only the mint's transfer hook authority can set up the hook, and becomes the whitelist admin.
+pub(crate) fn new_hook_config(mint: &InterfaceAccount<Mint>, authority: Pubkey, bump: u8) -> Result<HookConfig> {
+    let transfer_hook = get_mint_extension_data::<TransferHookExtension>(&mint.to_account_info())?;
+    require!(
+        Option::<Pubkey>::from(transfer_hook.authority) == Some(authority),
+        WhitelistError::NotTransferHookAuthority
+    );
+
+    Ok(HookConfig {
+        mint: mint.key(),
+        admin: authority,
+        pending_admin: None,
+        bump,
+    })
+}
+
+// For mints whose hook was set up before the config existed
+#[derive(Accounts)]
+pub struct InitHookConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"hook-config", mint.key().as_ref()],
+        space = 8 + HookConfig::INIT_SPACE,
+        bump,
+    )]
+    pub hook_config: Account<'info, HookConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitHookConfig<'info> {
+    pub fn init_hook_config(&mut self, bumps: &InitHookConfigBumps) -> Result<()> {
+        let hook_config = new_hook_config(&self.mint, self.authority.key(), bumps.hook_config)?;
+        self.hook_config.set_inner(hook_config);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct TransferAdmin<'info> {
+    pub admin: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        has_one = admin @ WhitelistError::NotAdmin,
+        seeds = [b"hook-config", mint.key().as_ref()],
+        bump = hook_config.bump,
+    )]
+    pub hook_config: Account<'info, HookConfig>,
+}
+
+impl<'info> TransferAdmin<'info> {
+    // the new admin has to accept before anything changes hands,
+    // proposing the current admin again cancels a pending transfer
+    pub fn transfer_admin(&mut self, new_admin: Pubkey) -> Result<()> {
+        self.hook_config.pending_admin = (new_admin != self.admin.key()).then_some(new_admin);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub pending_admin: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        constraint = hook_config.pending_admin == Some(pending_admin.key()) @ WhitelistError::NotPendingAdmin,
+        seeds = [b"hook-config", mint.key().as_ref()],
+        bump = hook_config.bump,
+    )]
+    pub hook_config: Account<'info, HookConfig>,
+}
+
+impl<'info> AcceptAdmin<'info> {
+    pub fn accept_admin(&mut self) -> Result<()> {
+        self.hook_config.admin = self.pending_admin.key();
+        self.hook_config.pending_admin = None;
+        Ok(())
+    }
+}
@@ -6,6 +6,8 @@ use spl_tlv_account_resolution::{
     state::ExtraAccountMetaList
 };
 
+use crate::{error::WhitelistError, instructions::admin::new_hook_config, states::HookConfig};
+
 #[derive(Accounts)]
 pub struct InitializeExtraAccountMetaList<'info> {
     #[account(mut)]
@@ -23,6 +25,14 @@ pub struct InitializeExtraAccountMetaList<'info> {
     pub extra_account_meta_list: AccountInfo<'info>,
 
     pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"hook-config", mint.key().as_ref()],
+        space = 8 + HookConfig::INIT_SPACE,
+        bump,
+    )]
+    pub hook_config: Account<'info, HookConfig>,
     pub system_program: Program<'info, System>,
 }
 
@@ -35,13 +45,58 @@ impl<'info> InitializeExtraAccountMetaList<'info> {
                         Seed::Literal {
                             bytes: b"whitelist".to_vec(),
                         },
-                        Seed::AccountKey { index: 0 }  // Should refer to user pubkey
+                        Seed::AccountKey { index: 1 }, // mint
+                        Seed::AccountKey { index: 3 }  // source token owner
                     ],
                     false, // is_signer
                     false // is_writable
+                )?,
+                ExtraAccountMeta::new_with_seeds(
+                    &[
+                        Seed::Literal {
+                            bytes: b"usage".to_vec(),
+                        },
+                        Seed::AccountKey { index: 1 }, // mint
+                        Seed::AccountKey { index: 3 }  // source token owner
+                    ],
+                    false, // is_signer
+                    true // is_writable, the hook records what was sent
                 )?
             ]
         )
     }
 
+    pub fn init_hook_config(&mut self, bumps: &InitializeExtraAccountMetaListBumps) -> Result<()> {
+        let hook_config = new_hook_config(&self.mint, self.payer.key(), bumps.hook_config)?;
+        self.hook_config.set_inner(hook_config);
+        Ok(())
+    }
+
+}
+
+// Rewrites the list of a mint set up with an older layout, e.g. before entries were scoped per mint
+#[derive(Accounts)]
+pub struct UpdateExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// CHECK: ExtraAccountMetaList Account, rewritten in `update_extra_account_meta_list`
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump,
+        realloc = ExtraAccountMetaList::size_of(
+            InitializeExtraAccountMetaList::extra_account_metas()?.len()
+        )?,
+        realloc::payer = admin,
+        realloc::zero = false,
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = admin @ WhitelistError::NotAdmin,
+        seeds = [b"hook-config", mint.key().as_ref()],
+        bump = hook_config.bump,
+    )]
+    pub hook_config: Account<'info, HookConfig>,
+    pub system_program: Program<'info, System>,
 }
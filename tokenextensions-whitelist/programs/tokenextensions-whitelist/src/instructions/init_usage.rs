@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::token_interface::Mint;
+
+use crate::states::{TransferUsage, Whitelist};
+
+// Creates the usage account of an entry whitelisted before transfer limits existed,
+// without it the hook can not resolve its accounts. Anyone can pay for it
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct InitUsage<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        seeds = [b"whitelist", mint.key().as_ref(), user.key().as_ref()],
+        bump = whitelist.bump,
+    )]
+    pub whitelist: Account<'info, Whitelist>,
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"usage", mint.key().as_ref(), user.key().as_ref()],
+        space = 8 + TransferUsage::INIT_SPACE,
+        bump,
+    )]
+    pub usage: Account<'info, TransferUsage>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitUsage<'info> {
+    pub fn init_usage(&mut self, bumps: InitUsageBumps) -> Result<()> {
+        self.usage.set_inner(TransferUsage {
+            used: 0,
+            last_update: 0,
+            bump: bumps.usage,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use anchor_lang::{prelude::*, Discriminator};
+
+use anchor_spl::token_interface::Mint;
+
+use crate::{error::WhitelistError, program::TokenextensionsWhitelist, states::{HookConfig, TransferUsage, Whitelist}};
+
+// Copies an entry from the old global `[b"whitelist", user]` layout
+// into the list of a single mint
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct MigrateWhitelist<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = admin @ WhitelistError::NotAdmin,
+        seeds = [b"hook-config", mint.key().as_ref()],
+        bump = hook_config.bump,
+    )]
+    pub hook_config: Account<'info, HookConfig>,
+    /// CHECK: entry in the old `Whitelist` layout, `is_whitelisted` then `bump`,
+    /// owner and discriminator are checked in `migrate_whitelist`
+    #[account(
+        seeds = [b"whitelist", user.key().as_ref()],
+        bump,
+    )]
+    pub legacy_whitelist: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [b"whitelist", mint.key().as_ref(), user.key().as_ref()],
+        space = 8 + Whitelist::INIT_SPACE,
+        bump,
+    )]
+    pub whitelist: Account<'info, Whitelist>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [b"usage", mint.key().as_ref(), user.key().as_ref()],
+        space = 8 + TransferUsage::INIT_SPACE,
+        bump,
+    )]
+    pub usage: Account<'info, TransferUsage>,
+    pub system_program: Program<'info, System>,
+}
+
+// Legacy entries are shared by every mint, so a mint admin can copy them but never close them
+fn legacy_is_whitelisted(legacy_whitelist: &AccountInfo) -> Result<bool> {
+    require_keys_eq!(*legacy_whitelist.owner, crate::ID, WhitelistError::InvalidLegacyEntry);
+    let data = legacy_whitelist.try_borrow_data()?;
+    require!(
+        data.len() >= 10 && data[..8] == *Whitelist::DISCRIMINATOR,
+        WhitelistError::InvalidLegacyEntry
+    );
+    Ok(data[8] != 0)
+}
+
+impl<'info> MigrateWhitelist<'info> {
+    pub fn migrate_whitelist(&mut self, bumps: MigrateWhitelistBumps) -> Result<()> {
+        let is_whitelisted = legacy_is_whitelisted(&self.legacy_whitelist)?;
+
+        // legacy entries had no validity window or limits
+        self.whitelist.set_inner(Whitelist {
+            is_whitelisted,
+            bump: bumps.whitelist,
+            valid_from: 0,
+            valid_until: 0,
+            max_per_transfer: 0,
+            daily_limit: 0,
+        });
+        self.usage.bump = bumps.usage;
+
+        Ok(())
+    }
+}
+
+// Closes an entry in the old global layout once every mint has migrated it,
+// only the program's upgrade authority can do this
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct CloseLegacyWhitelist<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, TokenextensionsWhitelist>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ WhitelistError::NotUpgradeAuthority)]
+    pub program_data: Account<'info, ProgramData>,
+    /// CHECK: entry in the old `Whitelist` layout, owner and discriminator are checked in `close_legacy`
+    #[account(
+        mut,
+        seeds = [b"whitelist", user.key().as_ref()],
+        bump,
+    )]
+    pub legacy_whitelist: UncheckedAccount<'info>,
+}
+
+impl<'info> CloseLegacyWhitelist<'info> {
+    pub fn close_legacy(&mut self) -> Result<()> {
+        legacy_is_whitelisted(&self.legacy_whitelist)?;
+
+        anchor_lang::common::close(
+            self.legacy_whitelist.to_account_info(),
+            self.authority.to_account_info(),
+        )
+    }
+}
@@ -1,21 +1,32 @@
 use anchor_lang::{ 
     prelude::*, 
 };
-use anchor_spl::token_interface::{
-    Mint, 
-    TokenInterface,
+use anchor_spl::{
+    associated_token::{self, AssociatedToken, Create},
+    token_interface::{
+        mint_to,
+        Mint, 
+        MintTo,
+        TokenInterface,
+    },
 };
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
 
-use crate::states::Whitelist;
+use crate::{error::WhitelistError, instructions::InitializeExtraAccountMetaList, states::HookConfig};
 
+// Creates a hooked mint with its ExtraAccountMetaList and HookConfig in one instruction,
+// the creator is the mint authority, the transfer hook authority and the whitelist admin
 #[derive(Accounts)]
+#[instruction(decimals: u8)]
 pub struct TokenFactory<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
     #[account(
         init,
         payer = user,
-        mint::decimals = 9,
+        mint::token_program = token_program,
+        mint::decimals = decimals,
         mint::authority = user,
         extensions::transfer_hook::authority = user,
         extensions::transfer_hook::program_id = crate::ID,
@@ -23,21 +34,84 @@ pub struct TokenFactory<'info> {
     pub mint: InterfaceAccount<'info, Mint>,
 
     /// CHECK: ExtraAccountMetaList Account, will be checked by the transfer hook
-    #[account(mut)]
+    #[account(
+        init,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump,
+        space = ExtraAccountMetaList::size_of(
+            InitializeExtraAccountMetaList::extra_account_metas()?.len()
+        )?,
+        payer = user
+    )]
     pub extra_account_meta_list: UncheckedAccount<'info>,
 
     #[account(
-        seeds = [b"whitelist"], 
-        bump
+        init,
+        payer = user,
+        seeds = [b"hook-config", mint.key().as_ref()],
+        space = 8 + HookConfig::INIT_SPACE,
+        bump,
     )]
-    pub blocklist: Account<'info, Whitelist>,
+    pub hook_config: Account<'info, HookConfig>,
+
+    /// CHECK: creator's associated token account, created by the associated token program,
+    /// only needed when minting an initial supply
+    #[account(mut)]
+    pub user_token_account: Option<UncheckedAccount<'info>>,
 
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 impl<'info> TokenFactory<'info> {
     pub fn init_mint(&mut self, bumps: &TokenFactoryBumps) -> Result<()> {
+        ExtraAccountMetaList::init::<ExecuteInstruction>(
+            &mut self.extra_account_meta_list.try_borrow_mut_data()?,
+            &InitializeExtraAccountMetaList::extra_account_metas()?
+        )?;
+
+        self.hook_config.set_inner(HookConfig {
+            mint: self.mint.key(),
+            admin: self.user.key(),
+            pending_admin: None,
+            bump: bumps.hook_config,
+        });
+
         Ok(())
     }
+
+    pub fn mint_initial_supply(&mut self, initial_supply: u64) -> Result<()> {
+        if initial_supply == 0 {
+            return Ok(());
+        }
+
+        let user_token_account = self
+            .user_token_account
+            .as_ref()
+            .ok_or(WhitelistError::MissingUserTokenAccount)?;
+
+        let cpi_program = self.associated_token_program.to_account_info();
+
+        let cpi_accounts = Create {
+            payer: self.user.to_account_info(),
+            associated_token: user_token_account.to_account_info(),
+            authority: self.user.to_account_info(),
+            mint: self.mint.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+
+        associated_token::create(CpiContext::new(cpi_program, cpi_accounts))?;
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = MintTo {
+            mint: self.mint.to_account_info(),
+            to: user_token_account.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+
+        mint_to(CpiContext::new(cpi_program, cpi_accounts), initial_supply)
+    }
 }
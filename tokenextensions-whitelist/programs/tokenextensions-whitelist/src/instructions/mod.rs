@@ -1,11 +1,17 @@
+pub mod admin;
 pub mod init_extra_account_meta;
+pub mod init_usage;
+pub mod migrate_whitelist;
 pub mod transfer_hook;
 pub mod whitelist_operations;
 pub mod mint_token;
 
 
 
+pub use admin::*;
 pub use init_extra_account_meta::*;
+pub use init_usage::*;
+pub use migrate_whitelist::*;
 pub use transfer_hook::*;
 pub use whitelist_operations::*;
 pub use mint_token::*;
@@ -1,6 +1,6 @@
 use std::cell::RefMut;
 
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, AccountsExit};
 
 use anchor_spl::{
     token_2022::spl_token_2022::{
@@ -17,7 +17,7 @@ use anchor_spl::{
     }
 };
 
-use crate::states::Whitelist;
+use crate::{error::WhitelistError, states::{TransferUsage, Whitelist}};
 
 #[derive(Accounts)]
 pub struct TransferHook<'info> {
@@ -40,27 +40,49 @@ pub struct TransferHook<'info> {
         bump
     )]
     pub extra_account_meta_list: UncheckedAccount<'info>,
+    /// CHECK: the owner's entry, read in `load_whitelist` so a missing one
+    /// fails with OwnerNotWhitelisted instead of AccountNotInitialized
     #[account(
-        seeds = [b"whitelist", owner.key().as_ref()], 
-        bump = whitelist.bump,
+        seeds = [b"whitelist", mint.key().as_ref(), owner.key().as_ref()], 
+        bump,
     )]
-    pub whitelist: Account<'info, Whitelist>,
+    pub whitelist: UncheckedAccount<'info>,
+    /// CHECK: the owner's usage, loaded only once the whitelist entry checks out
+    #[account(
+        mut,
+        seeds = [b"usage", mint.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub usage: UncheckedAccount<'info>,
 }
 
 impl<'info> TransferHook<'info> {
     /// This function is called when the transfer hook is executed.
-    pub fn transfer_hook(&mut self, _amount: u64) -> Result<()> {
+    pub fn transfer_hook(&mut self, amount: u64) -> Result<()> {
         // Fail this instruction if it is not called from within a transfer hook
         self.check_is_transferring()?;
 
-        // Challenge:
-        if !self.whitelist.is_whitelisted {
-            panic!("TransferHook: Owner is not whitelisted");
-        }
+        let now = Clock::get()?.unix_timestamp;
+        let whitelist = self.load_whitelist()?;
+        whitelist.check_valid(now)?;
+        whitelist.check_amount(amount)?;
+
+        let usage_info = self.usage.to_account_info();
+        let mut usage = Account::<TransferUsage>::try_from(&usage_info)?;
+        usage.record(amount, whitelist.daily_limit, now)?;
+        usage.exit(&crate::ID)?;
 
         Ok(())
     }
 
+    // an owner that was never added has no entry at all
+    fn load_whitelist(&self) -> Result<Whitelist> {
+        if self.whitelist.data_is_empty() || *self.whitelist.owner != crate::ID {
+            return err!(WhitelistError::OwnerNotWhitelisted);
+        }
+        Whitelist::try_deserialize(&mut &self.whitelist.try_borrow_data()?[..])
+    }
+
     /// Checks if the transfer hook is being executed during a transfer operation.
     fn check_is_transferring(&mut self) -> Result<()> {
         // Ensure that the source token account has the transfer hook extension enabled
@@ -82,10 +104,10 @@ impl<'info> TransferHook<'info> {
         let account_extension = account.get_extension_mut::<TransferHookAccount>()?;
     
         // Check if the account is in the middle of a transfer operation
-        if !bool::from(account_extension.transferring) {
-
-            panic!("TransferHook: Not transferring");
-        }
+        require!(
+            bool::from(account_extension.transferring),
+            WhitelistError::NotTransferring
+        );
     
         Ok(())
     }
@@ -2,48 +2,75 @@ use anchor_lang::{
     prelude::*,
 };
 
-use crate::states::Whitelist;
+use anchor_spl::token_interface::Mint;
+
+use crate::{error::WhitelistError, states::{HookConfig, TransferUsage, Whitelist}};
 
 
 #[derive(Accounts)]
 #[instruction(user: Pubkey)]
 pub struct WhitelistOperations<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
     #[account(
-        mut,
-        //address = 
+        has_one = admin @ WhitelistError::NotAdmin,
+        seeds = [b"hook-config", mint.key().as_ref()],
+        bump = hook_config.bump,
     )]
-    pub admin: Signer<'info>,
+    pub hook_config: Account<'info, HookConfig>,
     #[account(
         init_if_needed,
         payer = admin,
-        seeds = [b"whitelist", user.key().as_ref()],
+        seeds = [b"whitelist", mint.key().as_ref(), user.key().as_ref()],
         space = 8 + Whitelist::INIT_SPACE,
         bump,
     )]
     pub whitelist: Account<'info, Whitelist>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [b"usage", mint.key().as_ref(), user.key().as_ref()],
+        space = 8 + TransferUsage::INIT_SPACE,
+        bump,
+    )]
+    pub usage: Account<'info, TransferUsage>,
     pub system_program: Program<'info, System>,
 }
 
 
 impl<'info> WhitelistOperations<'info> {
-    pub fn add_to_whitelist(&mut self, bumps: WhitelistOperationsBumps) -> Result<()> {
-        // Challenge:
-        if !self.whitelist.is_whitelisted {
-            self.whitelist.set_inner(Whitelist {
-                is_whitelisted: true,
-                bump: bumps.whitelist
-            });
-        }
+    // adding an existing entry again renews it with the new window and limits,
+    // what was already used today still counts
+    pub fn add_to_whitelist(
+        &mut self,
+        valid_from: i64,
+        valid_until: i64,
+        max_per_transfer: u64,
+        daily_limit: u64,
+        bumps: WhitelistOperationsBumps,
+    ) -> Result<()> {
+        require!(
+            valid_until == 0 || valid_until > valid_from,
+            WhitelistError::InvalidValidity
+        );
+
+        self.whitelist.set_inner(Whitelist {
+            is_whitelisted: true,
+            bump: bumps.whitelist,
+            valid_from,
+            valid_until,
+            max_per_transfer,
+            daily_limit,
+        });
+        self.usage.bump = bumps.usage;
         Ok(())
     }
 
     pub fn remove_from_whitelist(&mut self, bumps: WhitelistOperationsBumps) -> Result<()> {
-        if self.whitelist.is_whitelisted {
-            self.whitelist.set_inner(Whitelist {
-                is_whitelisted: false,
-                bump: bumps.whitelist
-            });
-        }
+        self.whitelist.is_whitelisted = false;
+        self.whitelist.bump = bumps.whitelist;
+        self.usage.bump = bumps.usage;
         Ok(())
     }
 
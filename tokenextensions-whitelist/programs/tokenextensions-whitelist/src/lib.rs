@@ -2,8 +2,10 @@
 #![allow(deprecated)]
 use anchor_lang::prelude::*;
 
+mod error;
 mod instructions;
 mod states;
+mod tests;
 
 use instructions::*;
 
@@ -24,8 +26,15 @@ pub mod tokenextensions_whitelist {
     use super::*;
 
     // Challenge edited
-    pub fn add_to_whitelist(ctx: Context<WhitelistOperations>, user: Pubkey) -> Result<()> {
-        ctx.accounts.add_to_whitelist(ctx.bumps)
+    pub fn add_to_whitelist(
+        ctx: Context<WhitelistOperations>,
+        user: Pubkey,
+        valid_from: i64,
+        valid_until: i64,
+        max_per_transfer: u64,
+        daily_limit: u64,
+    ) -> Result<()> {
+        ctx.accounts.add_to_whitelist(valid_from, valid_until, max_per_transfer, daily_limit, ctx.bumps)
     }
 
     // Challenge edited
@@ -33,8 +42,38 @@ pub mod tokenextensions_whitelist {
         ctx.accounts.remove_from_whitelist(ctx.bumps)
     }
 
+    pub fn init_usage(ctx: Context<InitUsage>, user: Pubkey) -> Result<()> {
+        ctx.accounts.init_usage(ctx.bumps)
+    }
+
+    pub fn create_mint(ctx: Context<TokenFactory>, decimals: u8, initial_supply: u64) -> Result<()> {
+        ctx.accounts.init_mint(&ctx.bumps)?;
+        ctx.accounts.mint_initial_supply(initial_supply)
+    }
+
+    pub fn migrate_whitelist(ctx: Context<MigrateWhitelist>, user: Pubkey) -> Result<()> {
+        ctx.accounts.migrate_whitelist(ctx.bumps)
+    }
+
+    pub fn close_legacy_whitelist(ctx: Context<CloseLegacyWhitelist>, user: Pubkey) -> Result<()> {
+        ctx.accounts.close_legacy()
+    }
+
+    pub fn init_hook_config(ctx: Context<InitHookConfig>) -> Result<()> {
+        ctx.accounts.init_hook_config(&ctx.bumps)
+    }
+
+    pub fn transfer_admin(ctx: Context<TransferAdmin>, new_admin: Pubkey) -> Result<()> {
+        ctx.accounts.transfer_admin(new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        ctx.accounts.accept_admin()
+    }
+
     #[instruction(discriminator = InitializeExtraAccountMetaListInstruction::SPL_DISCRIMINATOR_SLICE)]
     pub fn initialize_transfer_hook(ctx: Context<InitializeExtraAccountMetaList>) -> Result<()> {
+        ctx.accounts.init_hook_config(&ctx.bumps)?;
 
         // Get the extra account metas for the transfer hook
         let extra_account_metas = InitializeExtraAccountMetaList::extra_account_metas()?;
@@ -48,6 +87,17 @@ pub mod tokenextensions_whitelist {
         Ok(())
     }
 
+    pub fn update_extra_account_meta_list(ctx: Context<UpdateExtraAccountMetaList>) -> Result<()> {
+        let extra_account_metas = InitializeExtraAccountMetaList::extra_account_metas()?;
+
+        // the account was resized to the current list, overwrite the old one
+        ExtraAccountMetaList::update::<ExecuteInstruction>(
+            &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
+            &extra_account_metas
+        )?;
+
+        Ok(())
+    }
 
     #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
     pub fn transfer_hook(ctx: Context<TransferHook>, amount: u64) -> Result<()> {
@@ -1,8 +1,75 @@
 use anchor_lang::prelude::*;
 
+use crate::error::WhitelistError;
+
 #[account]
 #[derive(InitSpace)]
 pub struct Whitelist {
     pub is_whitelisted: bool,
     pub bump: u8,
+    pub valid_from: i64, // 0 when valid right away
+    pub valid_until: i64, // 0 when the approval never lapses
+    pub max_per_transfer: u64, // 0 for no cap
+    pub daily_limit: u64, // 0 for no cap
+}
+
+impl Whitelist {
+    pub fn check_valid(&self, now: i64) -> Result<()> {
+        require!(self.is_whitelisted, WhitelistError::OwnerNotWhitelisted);
+        require!(now >= self.valid_from, WhitelistError::WhitelistNotYetValid);
+        require!(
+            self.valid_until == 0 || now < self.valid_until,
+            WhitelistError::WhitelistExpired
+        );
+        Ok(())
+    }
+
+    pub fn check_amount(&self, amount: u64) -> Result<()> {
+        require!(
+            self.max_per_transfer == 0 || amount <= self.max_per_transfer,
+            WhitelistError::TransferAmountTooLarge
+        );
+        Ok(())
+    }
+}
+
+pub const USAGE_WINDOW: i64 = 24 * 60 * 60;
+
+// What an owner has sent recently, written by the hook on every transfer
+#[account]
+#[derive(InitSpace)]
+pub struct TransferUsage {
+    pub used: u64,
+    pub last_update: i64,
+    pub bump: u8,
+}
+
+impl TransferUsage {
+    // usage drains linearly over USAGE_WINDOW, so there is no reset time
+    // to send a full limit just before and another just after
+    pub fn record(&mut self, amount: u64, daily_limit: u64, now: i64) -> Result<()> {
+        let elapsed = now.saturating_sub(self.last_update).clamp(0, USAGE_WINDOW);
+        // rounded down, whatever is left over still counts
+        let drained = (self.used as u128 * elapsed as u128 / USAGE_WINDOW as u128) as u64;
+
+        let used = (self.used - drained).saturating_add(amount);
+        require!(
+            daily_limit == 0 || used <= daily_limit,
+            WhitelistError::DailyLimitExceeded
+        );
+        self.used = used;
+        self.last_update = now;
+
+        Ok(())
+    }
+}
+
+// Who manages the whitelist of a hooked mint
+#[account]
+#[derive(InitSpace)]
+pub struct HookConfig {
+    pub mint: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Option<Pubkey>, // set by transfer_admin until accepted
+    pub bump: u8,
 }
@@ -0,0 +1,703 @@
+#[cfg(test)]
+mod tests {
+
+    use {
+        anchor_lang::{
+            AccountDeserialize,
+            InstructionData,
+            ToAccountMetas
+        },
+        anchor_spl::associated_token::{
+            self,
+            spl_associated_token_account
+        },
+        litesvm::LiteSVM,
+        solana_account::Account,
+        solana_instruction::{AccountMeta, Instruction},
+        solana_keypair::Keypair,
+        solana_message::Message,
+        solana_native_token::LAMPORTS_PER_SOL,
+        solana_pubkey::Pubkey,
+        solana_sdk_ids::{bpf_loader_upgradeable::ID as BPF_LOADER_UPGRADEABLE_ID, system_program::ID as SYSTEM_PROGRAM_ID},
+        solana_signer::Signer,
+        solana_transaction::Transaction,
+        spl_token_2022::{
+            extension::ExtensionType,
+            state::Mint,
+            ID as TOKEN_2022_PROGRAM_ID
+        },
+        std::path::PathBuf,
+        crate::{error::WhitelistError, states::{HookConfig, Whitelist}},
+    };
+
+    static PROGRAM_ID: Pubkey = crate::ID;
+
+    fn hook_config_pda(mint: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"hook-config", mint.as_ref()], &PROGRAM_ID).0
+    }
+
+    fn extra_account_meta_list_pda(mint: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"extra-account-metas", mint.as_ref()], &PROGRAM_ID).0
+    }
+
+    fn whitelist_pda(mint: &Pubkey, user: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"whitelist", mint.as_ref(), user.as_ref()], &PROGRAM_ID).0
+    }
+
+    fn usage_pda(mint: &Pubkey, user: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"usage", mint.as_ref(), user.as_ref()], &PROGRAM_ID).0
+    }
+
+    fn program_data_pda() -> Pubkey {
+        Pubkey::find_program_address(&[PROGRAM_ID.as_ref()], &BPF_LOADER_UPGRADEABLE_ID).0
+    }
+
+    // Helper: Deploy `elf` behind the upgradeable loader so the program has a ProgramData account
+    fn add_upgradeable_program(program: &mut LiteSVM, upgrade_authority: &Pubkey, elf: &[u8]) {
+        let program_data = program_data_pda();
+
+        // UpgradeableLoaderState::ProgramData { slot, upgrade_authority_address } followed by the ELF
+        let mut data = vec![3, 0, 0, 0];
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(upgrade_authority.as_ref());
+        data.extend_from_slice(elf);
+        program.set_account(program_data, Account {
+            lamports: program.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: BPF_LOADER_UPGRADEABLE_ID,
+            executable: false,
+            rent_epoch: 0,
+        }).unwrap();
+
+        // UpgradeableLoaderState::Program { programdata_address }
+        let mut data = vec![2, 0, 0, 0];
+        data.extend_from_slice(program_data.as_ref());
+        program.set_account(PROGRAM_ID, Account {
+            lamports: program.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: BPF_LOADER_UPGRADEABLE_ID,
+            executable: true,
+            rent_epoch: 0,
+        }).unwrap();
+    }
+
+    // Helper: Sign and send a single instruction, the first signer pays the fees
+    fn send_ix(program: &mut LiteSVM, ix: Instruction, signers: &[&Keypair]) -> litesvm::types::TransactionResult {
+        program.expire_blockhash();
+        let message = Message::new(&[ix], Some(&signers[0].pubkey()));
+        let transaction = Transaction::new(signers, message, program.latest_blockhash());
+        program.send_transaction(transaction)
+    }
+
+    // Helper: Assert the transaction failed with the given whitelist error
+    fn assert_whitelist_error(result: litesvm::types::TransactionResult, expected: WhitelistError) {
+        let err = result.expect_err("Transaction should have failed");
+        assert_eq!(
+            format!("{:?}", err.err),
+            format!("InstructionError(0, Custom({}))", u32::from(expected)),
+            "logs: {:#?}",
+            err.meta.logs
+        );
+    }
+
+    // Helper: Create a Token-2022 mint whose transfer hook points at this program,
+    // `authority` is both the mint and the transfer hook authority
+    fn create_hooked_mint(program: &mut LiteSVM, authority: &Keypair) -> Pubkey {
+        let mint = Keypair::new();
+        let space = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferHook]).unwrap();
+
+        let instructions = [
+            solana_system_interface::instruction::create_account(
+                &authority.pubkey(),
+                &mint.pubkey(),
+                program.minimum_balance_for_rent_exemption(space),
+                space as u64,
+                &TOKEN_2022_PROGRAM_ID,
+            ),
+            spl_token_2022::extension::transfer_hook::instruction::initialize(
+                &TOKEN_2022_PROGRAM_ID,
+                &mint.pubkey(),
+                Some(authority.pubkey()),
+                Some(PROGRAM_ID),
+            ).unwrap(),
+            spl_token_2022::instruction::initialize_mint2(
+                &TOKEN_2022_PROGRAM_ID,
+                &mint.pubkey(),
+                &authority.pubkey(),
+                None,
+                9,
+            ).unwrap(),
+        ];
+        let message = Message::new(&instructions, Some(&authority.pubkey()));
+        let transaction = Transaction::new(&[authority, &mint], message, program.latest_blockhash());
+        program.send_transaction(transaction).expect("Failed to create hooked mint");
+
+        mint.pubkey()
+    }
+
+    fn initialize_transfer_hook_ix(payer: &Pubkey, mint: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::InitializeExtraAccountMetaList {
+                payer: *payer,
+                extra_account_meta_list: extra_account_meta_list_pda(mint),
+                mint: *mint,
+                hook_config: hook_config_pda(mint),
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::InitializeTransferHook {}.data(),
+        }
+    }
+
+    fn setup() -> (LiteSVM, Keypair, Pubkey) {
+        let mut program = LiteSVM::new();
+        let admin = Keypair::new();
+
+        program
+            .airdrop(&admin.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop SOL to admin");
+
+        let so_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../../target/deploy/tokenextensions_whitelist.so");
+        let program_data = std::fs::read(so_path).expect("Failed to read program SO file");
+        // The admin also holds the upgrade authority
+        add_upgradeable_program(&mut program, &admin.pubkey(), &program_data);
+
+        // The mint's hook authority sets up the hook and becomes the whitelist admin
+        let mint = create_hooked_mint(&mut program, &admin);
+        let ix = initialize_transfer_hook_ix(&admin.pubkey(), &mint);
+        send_ix(&mut program, ix, &[&admin]).expect("Failed to initialize transfer hook");
+
+        (program, admin, mint)
+    }
+
+    // Helper: Create `owner`'s token account and mint it 1,000 tokens
+    fn fund(program: &mut LiteSVM, admin: &Keypair, mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+        let ata = associated_token::get_associated_token_address_with_program_id(owner, mint, &TOKEN_2022_PROGRAM_ID);
+        let instructions = [
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &admin.pubkey(),
+                owner,
+                mint,
+                &TOKEN_2022_PROGRAM_ID,
+            ),
+            spl_token_2022::instruction::mint_to(
+                &TOKEN_2022_PROGRAM_ID,
+                mint,
+                &ata,
+                &admin.pubkey(),
+                &[],
+                1_000_000_000_000,
+            ).unwrap(),
+        ];
+        let message = Message::new(&instructions, Some(&admin.pubkey()));
+        let transaction = Transaction::new(&[admin], message, program.latest_blockhash());
+        program.send_transaction(transaction).expect("Failed to fund token account");
+
+        ata
+    }
+
+    fn whitelist_ix(admin: &Pubkey, mint: &Pubkey, user: &Pubkey, add: bool) -> Instruction {
+        let mut ix = add_to_whitelist_ix(admin, mint, user, 0, 0);
+        if !add {
+            ix.data = crate::instruction::RemoveFromWhitelist { user: *user }.data();
+        }
+        ix
+    }
+
+    fn add_to_whitelist_ix(admin: &Pubkey, mint: &Pubkey, user: &Pubkey, valid_from: i64, valid_until: i64) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::WhitelistOperations {
+                admin: *admin,
+                mint: *mint,
+                hook_config: hook_config_pda(mint),
+                whitelist: whitelist_pda(mint, user),
+                usage: usage_pda(mint, user),
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::AddToWhitelist {
+                user: *user,
+                valid_from,
+                valid_until,
+                max_per_transfer: 0,
+                daily_limit: 0,
+            }.data(),
+        }
+    }
+
+    // Helper: transfer_checked with the accounts the hook resolves appended
+    fn transfer_ix(mint: &Pubkey, owner: &Pubkey, destination_owner: &Pubkey, amount: u64) -> Instruction {
+        let source = associated_token::get_associated_token_address_with_program_id(owner, mint, &TOKEN_2022_PROGRAM_ID);
+        let destination = associated_token::get_associated_token_address_with_program_id(destination_owner, mint, &TOKEN_2022_PROGRAM_ID);
+        let mut ix = spl_token_2022::instruction::transfer_checked(
+            &TOKEN_2022_PROGRAM_ID,
+            &source,
+            mint,
+            &destination,
+            owner,
+            &[],
+            amount,
+            9,
+        ).unwrap();
+        ix.accounts.extend([
+            AccountMeta::new_readonly(whitelist_pda(mint, owner), false),
+            AccountMeta::new(usage_pda(mint, owner), false),
+            AccountMeta::new_readonly(PROGRAM_ID, false),
+            AccountMeta::new_readonly(extra_account_meta_list_pda(mint), false),
+        ]);
+        ix
+    }
+
+    fn token_balance(program: &LiteSVM, account: &Pubkey) -> u64 {
+        let account = program.get_account(account).unwrap();
+        spl_token_2022::extension::StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+            .unwrap()
+            .base
+            .amount
+    }
+
+    #[test]
+    fn test_transfer_requires_whitelist() {
+        let (mut program, admin, mint) = setup();
+        let user = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        program.airdrop(&user.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        let source = fund(&mut program, &admin, &mint, &user.pubkey());
+        let destination = fund(&mut program, &admin, &mint, &recipient);
+
+        let ix = whitelist_ix(&admin.pubkey(), &mint, &user.pubkey(), true);
+        send_ix(&mut program, ix, &[&admin]).unwrap();
+        let ix = transfer_ix(&mint, &user.pubkey(), &recipient, 1_000_000_000);
+        send_ix(&mut program, ix, &[&user]).unwrap();
+        assert_eq!(token_balance(&program, &source), 999_000_000_000);
+        assert_eq!(token_balance(&program, &destination), 1_001_000_000_000);
+
+        let ix = whitelist_ix(&admin.pubkey(), &mint, &user.pubkey(), false);
+        send_ix(&mut program, ix, &[&admin]).unwrap();
+        let ix = transfer_ix(&mint, &user.pubkey(), &recipient, 1_000_000_000);
+        assert_whitelist_error(send_ix(&mut program, ix, &[&user]), WhitelistError::OwnerNotWhitelisted);
+
+        // an owner that was never added has no entry, and gets the same error
+        let stranger = Keypair::new();
+        program.airdrop(&stranger.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        fund(&mut program, &admin, &mint, &stranger.pubkey());
+        let ix = transfer_ix(&mint, &stranger.pubkey(), &recipient, 1_000_000_000);
+        assert_whitelist_error(send_ix(&mut program, ix, &[&stranger]), WhitelistError::OwnerNotWhitelisted);
+    }
+
+    #[test]
+    fn test_hook_outside_transfer() {
+        let (mut program, admin, mint) = setup();
+        let user = Keypair::new();
+        let source = fund(&mut program, &admin, &mint, &user.pubkey());
+        let destination = fund(&mut program, &admin, &mint, &Pubkey::new_unique());
+        let ix = whitelist_ix(&admin.pubkey(), &mint, &user.pubkey(), true);
+        send_ix(&mut program, ix, &[&admin]).unwrap();
+
+        // calling the hook directly instead of through a transfer
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::TransferHook {
+                source_token: source,
+                mint,
+                destination_token: destination,
+                owner: user.pubkey(),
+                extra_account_meta_list: extra_account_meta_list_pda(&mint),
+                whitelist: whitelist_pda(&mint, &user.pubkey()),
+                usage: usage_pda(&mint, &user.pubkey()),
+            }.to_account_metas(None),
+            data: crate::instruction::TransferHook { amount: 1 }.data(),
+        };
+        assert_whitelist_error(send_ix(&mut program, ix, &[&admin]), WhitelistError::NotTransferring);
+    }
+
+    #[test]
+    fn test_admin_only() {
+        let (mut program, admin, mint) = setup();
+        let intruder = Keypair::new();
+        program.airdrop(&intruder.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        // only the hook authority can set up a mint
+        let other_mint = create_hooked_mint(&mut program, &admin);
+        let ix = initialize_transfer_hook_ix(&intruder.pubkey(), &other_mint);
+        assert_whitelist_error(send_ix(&mut program, ix, &[&intruder]), WhitelistError::NotTransferHookAuthority);
+
+        let hook_config = HookConfig::try_deserialize(
+            &mut program.get_account(&hook_config_pda(&mint)).unwrap().data.as_ref()
+        ).unwrap();
+        assert_eq!(hook_config.admin, admin.pubkey());
+        assert_eq!(hook_config.mint, mint);
+
+        let ix = whitelist_ix(&intruder.pubkey(), &mint, &intruder.pubkey(), true);
+        assert_whitelist_error(send_ix(&mut program, ix, &[&intruder]), WhitelistError::NotAdmin);
+    }
+
+    #[test]
+    fn test_init_hook_config() {
+        let (mut program, admin, mint) = setup();
+        let intruder = Keypair::new();
+        program.airdrop(&intruder.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        // a mint whose hook was set up before the config existed
+        program.set_account(hook_config_pda(&mint), Account::default()).unwrap();
+        let init_hook_config_ix = |authority: &Pubkey| Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::InitHookConfig {
+                authority: *authority,
+                mint,
+                hook_config: hook_config_pda(&mint),
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::InitHookConfig {}.data(),
+        };
+
+        assert_whitelist_error(
+            send_ix(&mut program, init_hook_config_ix(&intruder.pubkey()), &[&intruder]),
+            WhitelistError::NotTransferHookAuthority,
+        );
+        send_ix(&mut program, init_hook_config_ix(&admin.pubkey()), &[&admin]).unwrap();
+
+        let hook_config = HookConfig::try_deserialize(
+            &mut program.get_account(&hook_config_pda(&mint)).unwrap().data.as_ref()
+        ).unwrap();
+        assert_eq!(hook_config.admin, admin.pubkey());
+        let ix = whitelist_ix(&admin.pubkey(), &mint, &intruder.pubkey(), true);
+        send_ix(&mut program, ix, &[&admin]).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_whitelist() {
+        let (mut program, admin, mint) = setup();
+        let user = Pubkey::new_unique();
+
+        // an entry in the old global layout
+        let (legacy, bump) = Pubkey::find_program_address(&[b"whitelist", user.as_ref()], &PROGRAM_ID);
+        // discriminator, is_whitelisted, bump
+        let mut data = <Whitelist as anchor_lang::Discriminator>::DISCRIMINATOR.to_vec();
+        data.extend([1, bump]);
+        program.set_account(legacy, Account {
+            lamports: program.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        }).unwrap();
+
+        let migrate_ix = |admin: &Pubkey| Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::MigrateWhitelist {
+                admin: *admin,
+                mint,
+                hook_config: hook_config_pda(&mint),
+                legacy_whitelist: legacy,
+                whitelist: whitelist_pda(&mint, &user),
+                usage: usage_pda(&mint, &user),
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::MigrateWhitelist { user }.data(),
+        };
+        let close_legacy_ix = |authority: &Pubkey| Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::CloseLegacyWhitelist {
+                authority: *authority,
+                program: PROGRAM_ID,
+                program_data: program_data_pda(),
+                legacy_whitelist: legacy,
+            }.to_account_metas(None),
+            data: crate::instruction::CloseLegacyWhitelist { user }.data(),
+        };
+
+        let intruder = Keypair::new();
+        program.airdrop(&intruder.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        assert_whitelist_error(send_ix(&mut program, migrate_ix(&intruder.pubkey()), &[&intruder]), WhitelistError::NotAdmin);
+
+        send_ix(&mut program, migrate_ix(&admin.pubkey()), &[&admin]).unwrap();
+
+        let whitelist = Whitelist::try_deserialize(
+            &mut program.get_account(&whitelist_pda(&mint, &user)).unwrap().data.as_ref()
+        ).unwrap();
+        assert!(whitelist.is_whitelisted);
+        assert_eq!((whitelist.valid_from, whitelist.valid_until), (0, 0));
+        assert_eq!((whitelist.max_per_transfer, whitelist.daily_limit), (0, 0));
+
+        // the admin of another mint can migrate the same entry, but not close it
+        let other_admin = Keypair::new();
+        program.airdrop(&other_admin.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        let other_mint = create_hooked_mint(&mut program, &other_admin);
+        send_ix(&mut program, initialize_transfer_hook_ix(&other_admin.pubkey(), &other_mint), &[&other_admin]).unwrap();
+        assert_whitelist_error(
+            send_ix(&mut program, close_legacy_ix(&other_admin.pubkey()), &[&other_admin]),
+            WhitelistError::NotUpgradeAuthority,
+        );
+        assert!(program.get_account(&legacy).is_some_and(|account| account.lamports > 0));
+
+        send_ix(&mut program, close_legacy_ix(&admin.pubkey()), &[&admin]).unwrap();
+        assert!(program.get_account(&legacy).map_or(true, |account| account.lamports == 0));
+    }
+
+    #[test]
+    fn test_create_mint() {
+        let (mut program, _, _) = setup();
+        let creator = Keypair::new();
+        program.airdrop(&creator.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        let mint = Keypair::new();
+        let creator_ata = associated_token::get_associated_token_address_with_program_id(
+            &creator.pubkey(), &mint.pubkey(), &TOKEN_2022_PROGRAM_ID
+        );
+
+        let create_mint_ix = |user_token_account: Option<Pubkey>, initial_supply: u64| Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::TokenFactory {
+                user: creator.pubkey(),
+                mint: mint.pubkey(),
+                extra_account_meta_list: extra_account_meta_list_pda(&mint.pubkey()),
+                hook_config: hook_config_pda(&mint.pubkey()),
+                user_token_account,
+                system_program: SYSTEM_PROGRAM_ID,
+                token_program: TOKEN_2022_PROGRAM_ID,
+                associated_token_program: spl_associated_token_account::ID,
+            }.to_account_metas(None),
+            data: crate::instruction::CreateMint { decimals: 9, initial_supply }.data(),
+        };
+
+        let ix = create_mint_ix(None, 1_000);
+        assert_whitelist_error(send_ix(&mut program, ix, &[&creator, &mint]), WhitelistError::MissingUserTokenAccount);
+
+        let ix = create_mint_ix(Some(creator_ata), 1_000_000_000_000);
+        send_ix(&mut program, ix, &[&creator, &mint]).unwrap();
+
+        // the hook points at this program and the creator administers the whitelist
+        let mint_account = program.get_account(&mint.pubkey()).unwrap();
+        let mint_state = spl_token_2022::extension::StateWithExtensions::<Mint>::unpack(&mint_account.data).unwrap();
+        let transfer_hook = mint_state
+            .get_extension::<spl_token_2022::extension::transfer_hook::TransferHook>()
+            .unwrap();
+        assert_eq!(Option::<Pubkey>::from(transfer_hook.program_id), Some(PROGRAM_ID));
+        assert_eq!(Option::<Pubkey>::from(transfer_hook.authority), Some(creator.pubkey()));
+        assert_eq!(token_balance(&program, &creator_ata), 1_000_000_000_000);
+
+        let hook_config = HookConfig::try_deserialize(
+            &mut program.get_account(&hook_config_pda(&mint.pubkey())).unwrap().data.as_ref()
+        ).unwrap();
+        assert_eq!(hook_config.admin, creator.pubkey());
+
+        // transfers go through the hook straight away
+        let recipient = Pubkey::new_unique();
+        let destination = fund(&mut program, &creator, &mint.pubkey(), &recipient);
+        let ix = transfer_ix(&mint.pubkey(), &creator.pubkey(), &recipient, 1_000_000_000);
+        assert!(send_ix(&mut program, ix, &[&creator]).is_err());
+        let ix = whitelist_ix(&creator.pubkey(), &mint.pubkey(), &creator.pubkey(), true);
+        send_ix(&mut program, ix, &[&creator]).unwrap();
+        let ix = transfer_ix(&mint.pubkey(), &creator.pubkey(), &recipient, 1_000_000_000);
+        send_ix(&mut program, ix, &[&creator]).unwrap();
+        assert_eq!(token_balance(&program, &destination), 1_001_000_000_000);
+    }
+
+    // Helper: Move the clock to `unix_timestamp`
+    fn warp_to(program: &mut LiteSVM, unix_timestamp: i64) {
+        let mut clock = program.get_sysvar::<anchor_lang::prelude::Clock>();
+        clock.unix_timestamp = unix_timestamp;
+        program.set_sysvar(&clock);
+    }
+
+    #[test]
+    fn test_whitelist_validity_window() {
+        let (mut program, admin, mint) = setup();
+        let user = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        program.airdrop(&user.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        fund(&mut program, &admin, &mint, &user.pubkey());
+        fund(&mut program, &admin, &mint, &recipient);
+
+        let start = 1_700_000_000;
+        warp_to(&mut program, start);
+
+        let ix = add_to_whitelist_ix(&admin.pubkey(), &mint, &user.pubkey(), start + 100, start + 100);
+        assert_whitelist_error(send_ix(&mut program, ix, &[&admin]), WhitelistError::InvalidValidity);
+
+        // approved from start + 100 until start + 200
+        let ix = add_to_whitelist_ix(&admin.pubkey(), &mint, &user.pubkey(), start + 100, start + 200);
+        send_ix(&mut program, ix, &[&admin]).unwrap();
+
+        // (time, expected error)
+        let checks = [
+            (start + 99, Some(WhitelistError::WhitelistNotYetValid)),
+            (start + 100, None),
+            (start + 199, None),
+            (start + 200, Some(WhitelistError::WhitelistExpired)),
+            (start + 10_000, Some(WhitelistError::WhitelistExpired)),
+        ];
+        for (now, expected) in checks {
+            warp_to(&mut program, now);
+            let ix = transfer_ix(&mint, &user.pubkey(), &recipient, 1);
+            let result = send_ix(&mut program, ix, &[&user]);
+            match expected {
+                Some(error) => assert_whitelist_error(result, error),
+                None => assert!(result.is_ok(), "transfer at {} failed: {:?}", now, result.err()),
+            }
+        }
+
+        // renewing the approval without an end date
+        let ix = add_to_whitelist_ix(&admin.pubkey(), &mint, &user.pubkey(), 0, 0);
+        send_ix(&mut program, ix, &[&admin]).unwrap();
+        let ix = transfer_ix(&mint, &user.pubkey(), &recipient, 1);
+        send_ix(&mut program, ix, &[&user]).unwrap();
+    }
+
+    fn add_with_limits_ix(admin: &Pubkey, mint: &Pubkey, user: &Pubkey, max_per_transfer: u64, daily_limit: u64) -> Instruction {
+        let mut ix = add_to_whitelist_ix(admin, mint, user, 0, 0);
+        ix.data = crate::instruction::AddToWhitelist {
+            user: *user,
+            valid_from: 0,
+            valid_until: 0,
+            max_per_transfer,
+            daily_limit,
+        }.data();
+        ix
+    }
+
+    #[test]
+    fn test_transfer_limits() {
+        let (mut program, admin, mint) = setup();
+        let user = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        program.airdrop(&user.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        let source = fund(&mut program, &admin, &mint, &user.pubkey());
+        fund(&mut program, &admin, &mint, &recipient);
+
+        let start = 1_700_000_000;
+        warp_to(&mut program, start);
+
+        // at most 40 per transfer and 100 a day
+        let ix = add_with_limits_ix(&admin.pubkey(), &mint, &user.pubkey(), 40, 100);
+        send_ix(&mut program, ix, &[&admin]).unwrap();
+
+        // (time, amount, expected error)
+        let transfers = [
+            (start, 41, Some(WhitelistError::TransferAmountTooLarge)),
+            (start, 40, None),
+            (start + 10, 40, None),
+            (start + 20, 21, Some(WhitelistError::DailyLimitExceeded)),
+            (start + 30, 20, None),
+            (start + 40, 1, Some(WhitelistError::DailyLimitExceeded)),
+            // half a day later half of the 100 has drained
+            (start + 30 + crate::states::USAGE_WINDOW / 2, 40, None),
+            (start + 30 + crate::states::USAGE_WINDOW / 2, 11, Some(WhitelistError::DailyLimitExceeded)),
+            (start + 30 + crate::states::USAGE_WINDOW / 2, 10, None),
+            // and all of it a full day later
+            (start + 30 + crate::states::USAGE_WINDOW * 3 / 2, 40, None),
+        ];
+        let mut sent = 0;
+        for (now, amount, expected) in transfers {
+            warp_to(&mut program, now);
+            let ix = transfer_ix(&mint, &user.pubkey(), &recipient, amount);
+            let result = send_ix(&mut program, ix, &[&user]);
+            match expected {
+                Some(error) => assert_whitelist_error(result, error),
+                None => {
+                    assert!(result.is_ok(), "transfer of {} at {} failed: {:?}", amount, now, result.err());
+                    sent += amount;
+                }
+            }
+        }
+        assert_eq!(token_balance(&program, &source), 1_000_000_000_000 - sent);
+
+        let usage = crate::states::TransferUsage::try_deserialize(
+            &mut program.get_account(&usage_pda(&mint, &user.pubkey())).unwrap().data.as_ref()
+        ).unwrap();
+        assert_eq!(usage.used, 40);
+        assert_eq!(usage.last_update, start + 30 + crate::states::USAGE_WINDOW * 3 / 2);
+
+        // lifting the limits takes effect on the next transfer
+        let ix = add_with_limits_ix(&admin.pubkey(), &mint, &user.pubkey(), 0, 0);
+        send_ix(&mut program, ix, &[&admin]).unwrap();
+        let ix = transfer_ix(&mint, &user.pubkey(), &recipient, 500);
+        send_ix(&mut program, ix, &[&user]).unwrap();
+    }
+
+    #[test]
+    fn test_init_usage() {
+        let (mut program, admin, mint) = setup();
+        let user = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        program.airdrop(&user.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        fund(&mut program, &admin, &mint, &user.pubkey());
+        fund(&mut program, &admin, &mint, &recipient);
+
+        // an entry whitelisted before usage accounts existed
+        let ix = whitelist_ix(&admin.pubkey(), &mint, &user.pubkey(), true);
+        send_ix(&mut program, ix, &[&admin]).unwrap();
+        program.set_account(usage_pda(&mint, &user.pubkey()), Account::default()).unwrap();
+        let ix = transfer_ix(&mint, &user.pubkey(), &recipient, 1_000_000_000);
+        assert!(send_ix(&mut program, ix, &[&user]).is_err());
+
+        // anyone can create it, but only for whitelisted entries
+        let stranger = Keypair::new();
+        program.airdrop(&stranger.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        let init_usage_ix = |user: &Pubkey| Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::InitUsage {
+                payer: stranger.pubkey(),
+                mint,
+                whitelist: whitelist_pda(&mint, user),
+                usage: usage_pda(&mint, user),
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::InitUsage { user: *user }.data(),
+        };
+        assert!(send_ix(&mut program, init_usage_ix(&Pubkey::new_unique()), &[&stranger]).is_err());
+        send_ix(&mut program, init_usage_ix(&user.pubkey()), &[&stranger]).unwrap();
+
+        let ix = transfer_ix(&mint, &user.pubkey(), &recipient, 1_000_000_000);
+        send_ix(&mut program, ix, &[&user]).unwrap();
+    }
+
+    #[test]
+    fn test_update_extra_account_meta_list() {
+        use spl_tlv_account_resolution::{account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList};
+        use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+        let (mut program, admin, mint) = setup();
+        let user = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        program.airdrop(&user.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        fund(&mut program, &admin, &mint, &user.pubkey());
+        fund(&mut program, &admin, &mint, &recipient);
+        let ix = whitelist_ix(&admin.pubkey(), &mint, &user.pubkey(), true);
+        send_ix(&mut program, ix, &[&admin]).unwrap();
+
+        // a list from before entries were scoped per mint
+        let legacy_metas = [ExtraAccountMeta::new_with_seeds(
+            &[Seed::Literal { bytes: b"whitelist".to_vec() }, Seed::AccountKey { index: 3 }],
+            false,
+            false,
+        ).unwrap()];
+        let mut data = vec![0; ExtraAccountMetaList::size_of(legacy_metas.len()).unwrap()];
+        ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &legacy_metas).unwrap();
+        let mut account = program.get_account(&extra_account_meta_list_pda(&mint)).unwrap();
+        account.data = data;
+        program.set_account(extra_account_meta_list_pda(&mint), account).unwrap();
+
+        let ix = transfer_ix(&mint, &user.pubkey(), &recipient, 1_000_000_000);
+        assert!(send_ix(&mut program, ix, &[&user]).is_err());
+
+        let update_ix = |admin: &Pubkey| Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::UpdateExtraAccountMetaList {
+                admin: *admin,
+                extra_account_meta_list: extra_account_meta_list_pda(&mint),
+                mint,
+                hook_config: hook_config_pda(&mint),
+                system_program: SYSTEM_PROGRAM_ID,
+            }.to_account_metas(None),
+            data: crate::instruction::UpdateExtraAccountMetaList {}.data(),
+        };
+        assert_whitelist_error(send_ix(&mut program, update_ix(&user.pubkey()), &[&user]), WhitelistError::NotAdmin);
+        send_ix(&mut program, update_ix(&admin.pubkey()), &[&admin]).unwrap();
+
+        let ix = transfer_ix(&mint, &user.pubkey(), &recipient, 1_000_000_000);
+        send_ix(&mut program, ix, &[&user]).unwrap();
+    }
+}
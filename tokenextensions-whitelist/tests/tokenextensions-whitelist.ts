@@ -16,6 +16,7 @@ import {
 } from "@solana/spl-token";
 import { SendTransactionError, SystemProgram, Transaction, sendAndConfirmTransaction } from '@solana/web3.js';
 import { TokenextensionsWhitelist } from "../target/types/tokenextensions_whitelist";
+import { assert } from "chai";
 
 
 describe("whitelist-transfer-hook", () => {
@@ -58,9 +59,26 @@ describe("whitelist-transfer-hook", () => {
   );
   console.log(program.programId);
 
+  // Whitelist admin of the mint, set when the ExtraAccountMetaList is created
+  const [hookConfig] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from('hook-config'), mint2022.publicKey.toBuffer()],
+    program.programId,
+  );
+
   const whitelist = anchor.web3.PublicKey.findProgramAddressSync(
     [
       Buffer.from("whitelist"),
+      mint2022.publicKey.toBuffer(),
+      provider.publicKey.toBuffer()
+    ],
+    program.programId
+  )[0];
+
+  // What the owner has sent today, written by the hook on every transfer
+  const usage = anchor.web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("usage"),
+      mint2022.publicKey.toBuffer(),
       provider.publicKey.toBuffer()
     ],
     program.programId
@@ -80,35 +98,6 @@ describe("whitelist-transfer-hook", () => {
   //   console.log("Transaction signature:", tx);
   // });
 
-  it("Add user to whitelist", async () => {
-    const tx = await program.methods.addToWhitelist(provider.publicKey)
-      // WhitelistOperations
-      .accountsPartial({
-        admin: provider.publicKey,
-        whitelist,
-        systemProgram: anchor.web3.SystemProgram.programId,
-
-      })
-      .rpc();
-
-    console.log("\nUser added to whitelist:", provider.publicKey.toBase58());
-    console.log("Transaction signature:", tx);
-  });
-
-
-  it("Remove user to whitelist", async () => {
-    const tx = await program.methods.removeFromWhitelist(provider.publicKey)
-      .accountsPartial({
-        admin: provider.publicKey,
-        whitelist,
-      })
-      .rpc();
-
-    console.log("\nUser removed from whitelist:", provider.publicKey.toBase58());
-    console.log("Transaction signature:", tx);
-  });
-
-
   it('Create Mint Account with Transfer Hook Extension', async () => {
     const extensions = [ExtensionType.TransferHook];
     const mintLen = getMintLen(extensions);
@@ -192,6 +181,7 @@ describe("whitelist-transfer-hook", () => {
         payer: wallet.publicKey,
         mint: mint2022.publicKey,
         extraAccountMetaList: extraAccountMetaListPDA,
+        hookConfig,
         systemProgram: SystemProgram.programId,
       })
       .instruction();
@@ -204,6 +194,117 @@ describe("whitelist-transfer-hook", () => {
     console.log('Transaction Signature:', txSig);
   });
 
+  it("Add user to whitelist", async () => {
+    const tx = await program.methods.addToWhitelist(provider.publicKey, new anchor.BN(0), new anchor.BN(0), new anchor.BN(0), new anchor.BN(0))
+      // WhitelistOperations
+      .accountsPartial({
+        admin: provider.publicKey,
+        mint: mint2022.publicKey,
+        hookConfig,
+        whitelist,
+        usage,
+        systemProgram: anchor.web3.SystemProgram.programId,
+
+      })
+      .rpc();
+
+    console.log("\nUser added to whitelist:", provider.publicKey.toBase58());
+    console.log("Transaction signature:", tx);
+  });
+
+
+  it("Remove user to whitelist", async () => {
+    const tx = await program.methods.removeFromWhitelist(provider.publicKey)
+      .accountsPartial({
+        admin: provider.publicKey,
+        mint: mint2022.publicKey,
+        hookConfig,
+        whitelist,
+        usage,
+      })
+      .rpc();
+
+    console.log("\nUser removed from whitelist:", provider.publicKey.toBase58());
+    console.log("Transaction signature:", tx);
+  });
+
+
+  it("Non-admin can not whitelist itself", async () => {
+    const intruder = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(intruder.publicKey, anchor.web3.LAMPORTS_PER_SOL),
+      'confirmed',
+    );
+
+    const intruderWhitelist = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("whitelist"), mint2022.publicKey.toBuffer(), intruder.publicKey.toBuffer()],
+      program.programId
+    )[0];
+
+    try {
+      await program.methods.addToWhitelist(intruder.publicKey, new anchor.BN(0), new anchor.BN(0), new anchor.BN(0), new anchor.BN(0))
+        .accountsPartial({
+          admin: intruder.publicKey,
+          mint: mint2022.publicKey,
+          hookConfig,
+          whitelist: intruderWhitelist,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([intruder])
+        .rpc();
+      assert.fail("Non-admin should not be able to whitelist");
+    } catch (error) {
+      assert.equal(error.error?.errorCode?.code, "NotAdmin");
+    }
+  });
+
+  it("Transfer admin in two steps", async () => {
+    const newAdmin = anchor.web3.Keypair.generate();
+
+    await program.methods.transferAdmin(newAdmin.publicKey)
+      .accountsPartial({
+        admin: provider.publicKey,
+        mint: mint2022.publicKey,
+        hookConfig,
+      })
+      .rpc();
+
+    // nothing changes until the new admin accepts
+    let config = await program.account.hookConfig.fetch(hookConfig);
+    assert.ok(config.admin.equals(provider.publicKey));
+    assert.ok(config.pendingAdmin.equals(newAdmin.publicKey));
+
+    await program.methods.acceptAdmin()
+      .accountsPartial({
+        pendingAdmin: newAdmin.publicKey,
+        mint: mint2022.publicKey,
+        hookConfig,
+      })
+      .signers([newAdmin])
+      .rpc();
+
+    config = await program.account.hookConfig.fetch(hookConfig);
+    assert.ok(config.admin.equals(newAdmin.publicKey));
+    assert.equal(config.pendingAdmin, null);
+
+    // and back, so the remaining tests keep the wallet as admin
+    await program.methods.transferAdmin(provider.publicKey)
+      .accountsPartial({
+        admin: newAdmin.publicKey,
+        mint: mint2022.publicKey,
+        hookConfig,
+      })
+      .signers([newAdmin])
+      .rpc();
+    await program.methods.acceptAdmin()
+      .accountsPartial({
+        pendingAdmin: provider.publicKey,
+        mint: mint2022.publicKey,
+        hookConfig,
+      })
+      .rpc();
+  });
+
   it('Transfer Hook with Extra Account Meta', async () => {
     // 1 tokens
     const amount = 1 * 10 ** 9;
@@ -226,18 +327,15 @@ describe("whitelist-transfer-hook", () => {
 
     const transaction = new Transaction().add(transferInstructionWithHelper);
 
+    // The owner was removed from the whitelist above, the hook rejects the transfer
     try {
-      // Send the transaction
-      const txSig = await sendAndConfirmTransaction(provider.connection, transaction, [wallet.payer], { skipPreflight: false });
-
-      console.log("\nTransfer Signature:", txSig);
+      await sendAndConfirmTransaction(provider.connection, transaction, [wallet.payer], { skipPreflight: false });
+      assert.fail("Transfer from a non-whitelisted owner should fail");
     }
     catch (error) {
-      if (error instanceof SendTransactionError) {
-        console.error("\nTransaction failed:", error.logs[4]);
-      } else {
-        console.error("\nUnexpected error:", error);
-      }
+      assert.instanceOf(error, SendTransactionError);
+      const anchorError = anchor.AnchorError.parse(error.logs);
+      assert.equal(anchorError?.error.errorCode.code, "OwnerNotWhitelisted");
     }
   });
 });